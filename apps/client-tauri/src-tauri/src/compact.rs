@@ -0,0 +1,844 @@
+// apps/client-tauri/src-tauri/src/compact.rs
+use std::f32::consts::{PI, TAU};
+
+use crate::protocol::{
+    CompactFormat, EntityDelta, EntityKind, EntitySnapshot, FlagSnapshot, GameModeState,
+    HealthStruct, LatencyStruct, LoadoutStruct, ProjectileKind, ProjectileSnapshot, ScoreStruct,
+    ScoreboardEntry, ServerEnvelope, SnapshotDelta, StaminaStruct, TeamId, TeamStruct,
+    TickSnapshot, Transform, VehicleKind, VehicleSnapshot,
+};
+use crate::sim::WorldBounds;
+
+// Compact snapshot encoding, offered by clients with `Hello { compact: true }`.
+// Positions are centimetres from the map's min corner in a u16, angles are
+// 16-bit fractions of a turn, and enums and optional components are packed
+// into bitfields. Integers are LEB128 varints (signed ones zigzagged).
+// Health, stamina and latency go as tenths, velocities as cm/s.
+
+const CM: f32 = 100.0;
+const TENTHS: f32 = 10.0;
+
+// Envelope header bits.
+const HAS_SNAPSHOT: u8 = 1 << 0;
+const HAS_DELTA: u8 = 1 << 1;
+
+// Entity component bits, in `EntitySnapshot` field order.
+const C_HEALTH: u16 = 1 << 0;
+const C_STAMINA: u16 = 1 << 1;
+const C_TEAM: u16 = 1 << 2;
+const C_SCORE: u16 = 1 << 3;
+const C_LOADOUT: u16 = 1 << 4;
+const C_NAME: u16 = 1 << 5;
+const C_LATENCY: u16 = 1 << 6;
+// Deltas only.
+const C_TRANSFORM: u16 = 1 << 7;
+const C_KIND: u16 = 1 << 8;
+// Entity kind and team value, packed above the component bits.
+const KIND_SHIFT: u16 = 10;
+const TEAM_SHIFT: u16 = 12;
+
+// Delta header bits.
+const D_GAME_STATE: u8 = 1 << 0;
+const D_SCOREBOARD: u8 = 1 << 1;
+
+/// Compact format for a map, or None if the map is too big for 16-bit
+/// centimetre positions.
+pub fn format_for(bounds: &WorldBounds) -> Option<CompactFormat> {
+    let max_span = u16::MAX as f32 / CM;
+    let fits = bounds.max_x - bounds.min_x <= max_span
+        && bounds.max_y - bounds.min_y <= max_span
+        && bounds.max_z - bounds.min_z <= max_span;
+    fits.then_some(CompactFormat {
+        origin_x: bounds.min_x,
+        origin_y: bounds.min_y,
+        origin_z: bounds.min_z,
+    })
+}
+
+pub fn encode_envelope(format: &CompactFormat, envelope: &ServerEnvelope) -> Vec<u8> {
+    let mut w = Writer { buf: Vec::with_capacity(256), format };
+    let mut header = 0;
+    if envelope.snapshot.is_some() {
+        header |= HAS_SNAPSHOT;
+    }
+    if envelope.delta.is_some() {
+        header |= HAS_DELTA;
+    }
+    w.u8(header);
+    w.varint(envelope.your_id.into());
+    if let Some(snapshot) = &envelope.snapshot {
+        w.snapshot(snapshot);
+    }
+    if let Some(delta) = &envelope.delta {
+        w.delta(delta);
+    }
+    w.buf
+}
+
+/// Just the snapshot part of an envelope, so it can be encoded once and
+/// shared between clients (see `snapshot_envelope`).
+pub fn encode_snapshot(format: &CompactFormat, snapshot: &TickSnapshot) -> Vec<u8> {
+    let mut w = Writer { buf: Vec::with_capacity(256), format };
+    w.snapshot(snapshot);
+    w.buf
+}
+
+/// Envelope around a snapshot from `encode_snapshot`. Same bytes as
+/// `encode_envelope` with only `your_id` and `snapshot` set.
+pub fn snapshot_envelope(format: &CompactFormat, your_id: u32, snapshot: &[u8]) -> Vec<u8> {
+    let mut w = Writer { buf: Vec::with_capacity(snapshot.len() + 6), format };
+    w.u8(HAS_SNAPSHOT);
+    w.varint(your_id.into());
+    w.buf.extend_from_slice(snapshot);
+    w.buf
+}
+
+/// Decode a compact envelope. Handshake-only fields (tokens, UDP port) are
+/// never sent compact and come back as None.
+// Client side of the protocol; the host only encodes.
+#[allow(dead_code)]
+pub fn decode_envelope(format: &CompactFormat, bytes: &[u8]) -> Result<ServerEnvelope, String> {
+    let mut r = Reader { bytes, pos: 0, format };
+    let header = r.u8()?;
+    let your_id = r.varint_u32()?;
+    let snapshot = if header & HAS_SNAPSHOT != 0 { Some(r.snapshot()?) } else { None };
+    let delta = if header & HAS_DELTA != 0 { Some(r.delta()?) } else { None };
+    if r.pos != bytes.len() {
+        return Err(format!("{} trailing bytes", bytes.len() - r.pos));
+    }
+    Ok(ServerEnvelope {
+        your_id,
+        snapshot,
+        session_token: None,
+        udp_port: None,
+        udp_token: None,
+        delta,
+        compact: None,
+    })
+}
+
+fn team_bits(team: TeamId) -> u8 {
+    match team {
+        TeamId::None => 0,
+        TeamId::TeamA => 1,
+        TeamId::TeamB => 2,
+    }
+}
+
+fn team_from(bits: u8) -> Result<TeamId, String> {
+    match bits & 0b11 {
+        0 => Ok(TeamId::None),
+        1 => Ok(TeamId::TeamA),
+        2 => Ok(TeamId::TeamB),
+        b => Err(format!("bad team {}", b)),
+    }
+}
+
+fn kind_bits(kind: EntityKind) -> u8 {
+    match kind {
+        EntityKind::Player => 0,
+        EntityKind::Vehicle => 1,
+        EntityKind::Projectile => 2,
+    }
+}
+
+fn kind_from(bits: u8) -> Result<EntityKind, String> {
+    match bits & 0b11 {
+        0 => Ok(EntityKind::Player),
+        1 => Ok(EntityKind::Vehicle),
+        2 => Ok(EntityKind::Projectile),
+        b => Err(format!("bad entity kind {}", b)),
+    }
+}
+
+fn component_mask(e: &EntitySnapshot) -> u16 {
+    let mut mask = 0;
+    for (present, bit) in [
+        (e.health.is_some(), C_HEALTH),
+        (e.stamina.is_some(), C_STAMINA),
+        (e.team.is_some(), C_TEAM),
+        (e.score.is_some(), C_SCORE),
+        (e.loadout.is_some(), C_LOADOUT),
+        (e.name.is_some(), C_NAME),
+        (e.latency.is_some(), C_LATENCY),
+    ] {
+        if present {
+            mask |= bit;
+        }
+    }
+    mask
+}
+
+struct Writer<'a> {
+    buf: Vec<u8>,
+    format: &'a CompactFormat,
+}
+
+impl Writer<'_> {
+    fn u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    fn u16(&mut self, v: u16) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn f32(&mut self, v: f32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn varint(&mut self, mut v: u64) {
+        while v >= 0x80 {
+            self.buf.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        self.buf.push(v as u8);
+    }
+
+    fn zigzag(&mut self, v: i64) {
+        self.varint(((v << 1) ^ (v >> 63)) as u64);
+    }
+
+    fn len(&mut self, n: usize) {
+        self.varint(n as u64);
+    }
+
+    fn str(&mut self, s: &str) {
+        self.len(s.len());
+        self.buf.extend_from_slice(s.as_bytes());
+    }
+
+    // Centimetres from `origin`, clamped to the map.
+    fn pos(&mut self, v: f32, origin: f32) {
+        self.u16(((v - origin) * CM).round().clamp(0.0, u16::MAX as f32) as u16);
+    }
+
+    fn position(&mut self, x: f32, y: f32, z: f32) {
+        let f = self.format;
+        self.pos(x, f.origin_x);
+        self.pos(y, f.origin_y);
+        self.pos(z, f.origin_z);
+    }
+
+    fn angle(&mut self, a: f32) {
+        let turns = a.rem_euclid(TAU) / TAU;
+        self.u16(((turns * 65536.0).round() as u32 & 0xFFFF) as u16);
+    }
+
+    fn tenths(&mut self, v: f32) {
+        self.zigzag((v * TENTHS).round() as i64);
+    }
+
+    fn ids(&mut self, ids: &[u32]) {
+        self.len(ids.len());
+        for id in ids {
+            self.varint((*id).into());
+        }
+    }
+
+    fn transform(&mut self, t: &Transform) {
+        self.position(t.x, t.y, t.z);
+        self.angle(t.yaw);
+        self.angle(t.pitch);
+    }
+
+    fn health(&mut self, h: &HealthStruct) {
+        self.tenths(h.current);
+        self.tenths(h.max);
+    }
+
+    fn stamina(&mut self, s: &StaminaStruct) {
+        self.tenths(s.current);
+        self.tenths(s.max);
+    }
+
+    fn score(&mut self, s: &ScoreStruct) {
+        self.varint(s.kills.into());
+        self.varint(s.deaths.into());
+    }
+
+    fn latency(&mut self, l: &LatencyStruct) {
+        self.tenths(l.rtt_ms);
+        self.tenths(l.jitter_ms);
+    }
+
+    fn entity(&mut self, e: &EntitySnapshot) {
+        let mut mask = component_mask(e) | (u16::from(kind_bits(e.kind)) << KIND_SHIFT);
+        if let Some(team) = &e.team {
+            mask |= u16::from(team_bits(team.id)) << TEAM_SHIFT;
+        }
+        self.varint(e.eid.into());
+        self.u16(mask);
+        self.transform(&e.transform);
+        if let Some(h) = &e.health {
+            self.health(h);
+        }
+        if let Some(s) = &e.stamina {
+            self.stamina(s);
+        }
+        if let Some(s) = &e.score {
+            self.score(s);
+        }
+        if let Some(l) = &e.loadout {
+            self.u8(l.class_id);
+        }
+        if let Some(name) = &e.name {
+            self.str(name);
+        }
+        if let Some(l) = &e.latency {
+            self.latency(l);
+        }
+    }
+
+    fn entity_delta(&mut self, d: &EntityDelta) {
+        let mut mask = 0;
+        for (present, bit) in [
+            (d.health.is_some(), C_HEALTH),
+            (d.stamina.is_some(), C_STAMINA),
+            (d.team.is_some(), C_TEAM),
+            (d.score.is_some(), C_SCORE),
+            (d.loadout.is_some(), C_LOADOUT),
+            (d.name.is_some(), C_NAME),
+            (d.latency.is_some(), C_LATENCY),
+            (d.transform.is_some(), C_TRANSFORM),
+            (d.kind.is_some(), C_KIND),
+        ] {
+            if present {
+                mask |= bit;
+            }
+        }
+        if let Some(kind) = d.kind {
+            mask |= u16::from(kind_bits(kind)) << KIND_SHIFT;
+        }
+        if let Some(team) = &d.team {
+            mask |= u16::from(team_bits(team.id)) << TEAM_SHIFT;
+        }
+        self.varint(d.eid.into());
+        self.u16(mask);
+        self.varint(d.cleared.into());
+        if let Some(t) = &d.transform {
+            self.transform(t);
+        }
+        if let Some(h) = &d.health {
+            self.health(h);
+        }
+        if let Some(s) = &d.stamina {
+            self.stamina(s);
+        }
+        if let Some(s) = &d.score {
+            self.score(s);
+        }
+        if let Some(l) = &d.loadout {
+            self.u8(l.class_id);
+        }
+        if let Some(name) = &d.name {
+            self.str(name);
+        }
+        if let Some(l) = &d.latency {
+            self.latency(l);
+        }
+    }
+
+    fn flag(&mut self, f: &FlagSnapshot) {
+        self.varint(f.id.into());
+        self.position(f.x, f.y, f.z);
+        self.u16((f.radius * CM).round().clamp(0.0, u16::MAX as f32) as u16);
+        self.u8(team_bits(f.owner));
+        self.f32(f.capture);
+    }
+
+    fn game_state(&mut self, g: &GameModeState) {
+        self.zigzag(g.team_a_tickets.into());
+        self.zigzag(g.team_b_tickets.into());
+        self.u8(u8::from(g.match_ended) | (team_bits(g.winner) << 1));
+    }
+
+    fn projectile(&mut self, p: &ProjectileSnapshot) {
+        self.varint(p.id.into());
+        self.u8(match p.kind {
+            ProjectileKind::Grenade => 0,
+            ProjectileKind::RifleGrenade => 1,
+        });
+        self.varint(p.owner.into());
+        for v in [p.vx, p.vy, p.vz] {
+            self.zigzag((v * CM).round() as i64);
+        }
+    }
+
+    fn vehicle(&mut self, v: &VehicleSnapshot) {
+        self.varint(v.id.into());
+        let kind = match v.kind {
+            VehicleKind::Jeep => 0,
+            VehicleKind::Tank => 1,
+        };
+        self.u8(kind | (u8::from(v.destroyed) << 1));
+        // Seat count, then which seats are taken, then their occupants.
+        self.u8(v.seats.len() as u8);
+        let occupied = v
+            .seats
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.is_some())
+            .fold(0u8, |bits, (i, _)| bits | (1 << i));
+        self.u8(occupied);
+        for id in v.seats.iter().flatten() {
+            self.varint((*id).into());
+        }
+    }
+
+    fn scoreboard(&mut self, entries: &[ScoreboardEntry]) {
+        self.len(entries.len());
+        for s in entries {
+            self.varint(s.eid.into());
+            self.str(&s.name);
+            self.u8(team_bits(s.team));
+            self.varint(s.kills.into());
+            self.varint(s.deaths.into());
+        }
+    }
+
+    fn snapshot(&mut self, s: &TickSnapshot) {
+        self.varint(s.tick);
+        self.len(s.entities.len());
+        s.entities.iter().for_each(|e| self.entity(e));
+        self.len(s.flags.len());
+        s.flags.iter().for_each(|f| self.flag(f));
+        self.game_state(&s.game_state);
+        self.len(s.projectiles.len());
+        s.projectiles.iter().for_each(|p| self.projectile(p));
+        self.len(s.vehicles.len());
+        s.vehicles.iter().for_each(|v| self.vehicle(v));
+        self.scoreboard(&s.scoreboard);
+    }
+
+    fn delta(&mut self, d: &SnapshotDelta) {
+        self.varint(d.baseline);
+        self.varint(d.tick);
+        let mut header = 0;
+        if d.game_state.is_some() {
+            header |= D_GAME_STATE;
+        }
+        if d.scoreboard.is_some() {
+            header |= D_SCOREBOARD;
+        }
+        self.u8(header);
+        self.len(d.spawned.len());
+        d.spawned.iter().for_each(|e| self.entity(e));
+        self.len(d.changed.len());
+        d.changed.iter().for_each(|e| self.entity_delta(e));
+        self.ids(&d.removed);
+        self.len(d.flags.len());
+        d.flags.iter().for_each(|f| self.flag(f));
+        if let Some(g) = &d.game_state {
+            self.game_state(g);
+        }
+        self.len(d.projectiles.len());
+        d.projectiles.iter().for_each(|p| self.projectile(p));
+        self.ids(&d.removed_projectiles);
+        self.len(d.vehicles.len());
+        d.vehicles.iter().for_each(|v| self.vehicle(v));
+        self.ids(&d.removed_vehicles);
+        if let Some(s) = &d.scoreboard {
+            self.scoreboard(s);
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    format: &'a CompactFormat,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Result<&[u8], String> {
+        let end = self.pos.checked_add(n).filter(|end| *end <= self.bytes.len());
+        let end = end.ok_or_else(|| format!("truncated at byte {}", self.pos))?;
+        let out = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let b = self.take(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        let b = self.take(4)?;
+        Ok(f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.u8()?;
+            v |= u64::from(b & 0x7F) << shift;
+            if b & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+        Err("varint too long".to_string())
+    }
+
+    fn varint_u32(&mut self) -> Result<u32, String> {
+        u32::try_from(self.varint()?).map_err(|e| e.to_string())
+    }
+
+    fn zigzag(&mut self) -> Result<i64, String> {
+        let v = self.varint()?;
+        Ok(((v >> 1) as i64) ^ -((v & 1) as i64))
+    }
+
+    // Lengths are bounded by what is left, so garbage can't allocate much.
+    fn len(&mut self) -> Result<usize, String> {
+        let n = self.varint()? as usize;
+        if n > self.bytes.len() - self.pos {
+            return Err(format!("bad length {}", n));
+        }
+        Ok(n)
+    }
+
+    fn str(&mut self) -> Result<String, String> {
+        let n = self.len()?;
+        String::from_utf8(self.take(n)?.to_vec()).map_err(|e| e.to_string())
+    }
+
+    fn position(&mut self) -> Result<(f32, f32, f32), String> {
+        let f = self.format;
+        let x = f.origin_x + f32::from(self.u16()?) / CM;
+        let y = f.origin_y + f32::from(self.u16()?) / CM;
+        let z = f.origin_z + f32::from(self.u16()?) / CM;
+        Ok((x, y, z))
+    }
+
+    // Back into (-PI, PI].
+    fn angle(&mut self) -> Result<f32, String> {
+        let a = f32::from(self.u16()?) / 65536.0 * TAU;
+        Ok(if a > PI { a - TAU } else { a })
+    }
+
+    fn tenths(&mut self) -> Result<f32, String> {
+        Ok(self.zigzag()? as f32 / TENTHS)
+    }
+
+    fn list<T>(&mut self, mut item: impl FnMut(&mut Self) -> Result<T, String>) -> Result<Vec<T>, String> {
+        let n = self.len()?;
+        (0..n).map(|_| item(self)).collect()
+    }
+
+    fn transform(&mut self) -> Result<Transform, String> {
+        let (x, y, z) = self.position()?;
+        Ok(Transform { x, y, z, yaw: self.angle()?, pitch: self.angle()? })
+    }
+
+    fn health(&mut self) -> Result<HealthStruct, String> {
+        Ok(HealthStruct { current: self.tenths()?, max: self.tenths()? })
+    }
+
+    fn stamina(&mut self) -> Result<StaminaStruct, String> {
+        Ok(StaminaStruct { current: self.tenths()?, max: self.tenths()? })
+    }
+
+    fn score(&mut self) -> Result<ScoreStruct, String> {
+        Ok(ScoreStruct { kills: self.varint_u32()?, deaths: self.varint_u32()? })
+    }
+
+    fn latency(&mut self) -> Result<LatencyStruct, String> {
+        Ok(LatencyStruct { rtt_ms: self.tenths()?, jitter_ms: self.tenths()? })
+    }
+
+    fn team(mask: u16) -> Result<TeamStruct, String> {
+        Ok(TeamStruct { id: team_from((mask >> TEAM_SHIFT) as u8)? })
+    }
+
+    fn entity(&mut self) -> Result<EntitySnapshot, String> {
+        let eid = self.varint_u32()?;
+        let mask = self.u16()?;
+        let has = |bit: u16| mask & bit != 0;
+        Ok(EntitySnapshot {
+            eid,
+            transform: self.transform()?,
+            health: if has(C_HEALTH) { Some(self.health()?) } else { None },
+            stamina: if has(C_STAMINA) { Some(self.stamina()?) } else { None },
+            team: if has(C_TEAM) { Some(Self::team(mask)?) } else { None },
+            score: if has(C_SCORE) { Some(self.score()?) } else { None },
+            loadout: if has(C_LOADOUT) { Some(LoadoutStruct { class_id: self.u8()? }) } else { None },
+            kind: kind_from((mask >> KIND_SHIFT) as u8)?,
+            name: if has(C_NAME) { Some(self.str()?) } else { None },
+            latency: if has(C_LATENCY) { Some(self.latency()?) } else { None },
+        })
+    }
+
+    fn entity_delta(&mut self) -> Result<EntityDelta, String> {
+        let eid = self.varint_u32()?;
+        let mask = self.u16()?;
+        let has = |bit: u16| mask & bit != 0;
+        let cleared = u16::try_from(self.varint()?).map_err(|e| e.to_string())?;
+        Ok(EntityDelta {
+            eid,
+            transform: if has(C_TRANSFORM) { Some(self.transform()?) } else { None },
+            health: if has(C_HEALTH) { Some(self.health()?) } else { None },
+            stamina: if has(C_STAMINA) { Some(self.stamina()?) } else { None },
+            team: if has(C_TEAM) { Some(Self::team(mask)?) } else { None },
+            score: if has(C_SCORE) { Some(self.score()?) } else { None },
+            loadout: if has(C_LOADOUT) { Some(LoadoutStruct { class_id: self.u8()? }) } else { None },
+            kind: if has(C_KIND) { Some(kind_from((mask >> KIND_SHIFT) as u8)?) } else { None },
+            name: if has(C_NAME) { Some(self.str()?) } else { None },
+            latency: if has(C_LATENCY) { Some(self.latency()?) } else { None },
+            cleared,
+        })
+    }
+
+    fn flag(&mut self) -> Result<FlagSnapshot, String> {
+        let id = self.varint_u32()?;
+        let (x, y, z) = self.position()?;
+        Ok(FlagSnapshot {
+            id,
+            x,
+            y,
+            z,
+            radius: f32::from(self.u16()?) / CM,
+            owner: team_from(self.u8()?)?,
+            capture: self.f32()?,
+        })
+    }
+
+    fn game_state(&mut self) -> Result<GameModeState, String> {
+        let team_a_tickets = i32::try_from(self.zigzag()?).map_err(|e| e.to_string())?;
+        let team_b_tickets = i32::try_from(self.zigzag()?).map_err(|e| e.to_string())?;
+        let bits = self.u8()?;
+        Ok(GameModeState {
+            team_a_tickets,
+            team_b_tickets,
+            match_ended: bits & 1 != 0,
+            winner: team_from(bits >> 1)?,
+        })
+    }
+
+    fn projectile(&mut self) -> Result<ProjectileSnapshot, String> {
+        let id = self.varint_u32()?;
+        let kind = match self.u8()? {
+            0 => ProjectileKind::Grenade,
+            1 => ProjectileKind::RifleGrenade,
+            b => return Err(format!("bad projectile kind {}", b)),
+        };
+        let owner = self.varint_u32()?;
+        Ok(ProjectileSnapshot {
+            id,
+            kind,
+            owner,
+            vx: self.zigzag()? as f32 / CM,
+            vy: self.zigzag()? as f32 / CM,
+            vz: self.zigzag()? as f32 / CM,
+        })
+    }
+
+    fn vehicle(&mut self) -> Result<VehicleSnapshot, String> {
+        let id = self.varint_u32()?;
+        let bits = self.u8()?;
+        let kind = if bits & 1 == 0 { VehicleKind::Jeep } else { VehicleKind::Tank };
+        let seat_count = self.u8()? as usize;
+        if seat_count > 8 {
+            return Err(format!("bad seat count {}", seat_count));
+        }
+        let occupied = self.u8()?;
+        let seats = (0..seat_count)
+            .map(|i| {
+                if occupied & (1 << i) != 0 {
+                    self.varint_u32().map(Some)
+                } else {
+                    Ok(None)
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(VehicleSnapshot { id, kind, seats, destroyed: bits & 2 != 0 })
+    }
+
+    fn scoreboard(&mut self) -> Result<Vec<ScoreboardEntry>, String> {
+        self.list(|r| {
+            Ok(ScoreboardEntry {
+                eid: r.varint_u32()?,
+                name: r.str()?,
+                team: team_from(r.u8()?)?,
+                kills: r.varint_u32()?,
+                deaths: r.varint_u32()?,
+            })
+        })
+    }
+
+    fn snapshot(&mut self) -> Result<TickSnapshot, String> {
+        let tick = self.varint()?;
+        Ok(TickSnapshot {
+            entities: self.list(Self::entity)?,
+            flags: self.list(Self::flag)?,
+            game_state: self.game_state()?,
+            projectiles: self.list(Self::projectile)?,
+            vehicles: self.list(Self::vehicle)?,
+            scoreboard: self.scoreboard()?,
+            tick,
+        })
+    }
+
+    fn delta(&mut self) -> Result<SnapshotDelta, String> {
+        let baseline = self.varint()?;
+        let tick = self.varint()?;
+        let header = self.u8()?;
+        Ok(SnapshotDelta {
+            baseline,
+            tick,
+            spawned: self.list(Self::entity)?,
+            changed: self.list(Self::entity_delta)?,
+            removed: self.list(Self::varint_u32)?,
+            flags: self.list(Self::flag)?,
+            game_state: if header & D_GAME_STATE != 0 { Some(self.game_state()?) } else { None },
+            projectiles: self.list(Self::projectile)?,
+            removed_projectiles: self.list(Self::varint_u32)?,
+            vehicles: self.list(Self::vehicle)?,
+            removed_vehicles: self.list(Self::varint_u32)?,
+            scoreboard: if header & D_SCOREBOARD != 0 { Some(self.scoreboard()?) } else { None },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delta;
+    use crate::maps::warehouse::world_bounds;
+    use crate::sim::SimState;
+    use std::collections::HashMap;
+
+    fn format() -> CompactFormat {
+        format_for(&world_bounds()).expect("warehouse fits")
+    }
+
+    fn envelope(snapshot: Option<TickSnapshot>, delta: Option<SnapshotDelta>) -> ServerEnvelope {
+        ServerEnvelope {
+            your_id: 7,
+            snapshot,
+            session_token: None,
+            udp_port: None,
+            udp_token: None,
+            delta,
+            compact: None,
+        }
+    }
+
+    // Half a centimetre of rounding, plus f32 error at map scale.
+    const POS_TOLERANCE: f32 = 0.5 / CM + 1e-4;
+
+    fn angle_diff(a: f32, b: f32) -> f32 {
+        let d = (a - b).rem_euclid(TAU);
+        d.min(TAU - d)
+    }
+
+    fn sim_with_players() -> SimState {
+        let mut sim = SimState::new();
+        for name in ["alpha", "bravo"] {
+            let conn = sim.allocate_connection_id();
+            sim.handle_join(conn, name, None).unwrap();
+        }
+        sim
+    }
+
+    #[test]
+    fn snapshot_round_trip_within_precision() {
+        let f = format();
+        let mut snapshot = sim_with_players().update(0.05, &HashMap::new());
+        let player = snapshot.entities.iter_mut().find(|e| e.kind == EntityKind::Player).unwrap();
+        player.transform = Transform { x: -12.345, y: 3.217, z: 99.994, yaw: -2.5, pitch: 1.2 };
+        player.health = Some(HealthStruct { current: 37.46, max: 100.0 });
+
+        let bytes = encode_envelope(&f, &envelope(Some(snapshot.clone()), None));
+        let full = rmp_serde::to_vec(&envelope(Some(snapshot.clone()), None)).unwrap();
+        assert!(bytes.len() < full.len(), "{} vs {} bytes", bytes.len(), full.len());
+
+        let decoded = decode_envelope(&f, &bytes).unwrap();
+        assert_eq!(decoded.your_id, 7);
+        let got = decoded.snapshot.unwrap();
+        assert_eq!(got.entities.len(), snapshot.entities.len());
+        for (a, b) in snapshot.entities.iter().zip(&got.entities) {
+            assert_eq!((a.eid, a.kind, &a.name), (b.eid, b.kind, &b.name));
+            assert!((a.transform.x - b.transform.x).abs() <= POS_TOLERANCE, "{:?} vs {:?}", a.transform, b.transform);
+            assert!((a.transform.y - b.transform.y).abs() <= POS_TOLERANCE);
+            assert!((a.transform.z - b.transform.z).abs() <= POS_TOLERANCE);
+            assert!(angle_diff(a.transform.yaw, b.transform.yaw) <= TAU / 65536.0);
+            assert!(angle_diff(a.transform.pitch, b.transform.pitch) <= TAU / 65536.0);
+            assert_eq!(a.team, b.team);
+            assert_eq!(a.score, b.score);
+            assert_eq!(a.loadout, b.loadout);
+            match (&a.health, &b.health) {
+                (Some(x), Some(y)) => assert!((x.current - y.current).abs() <= 0.05),
+                (x, y) => assert_eq!(x.is_none(), y.is_none()),
+            }
+        }
+        assert_eq!(got.vehicles, snapshot.vehicles);
+        assert_eq!(got.scoreboard, snapshot.scoreboard);
+        assert_eq!(got.game_state, snapshot.game_state);
+        assert_eq!(got.tick, snapshot.tick);
+        for (a, b) in snapshot.flags.iter().zip(&got.flags) {
+            assert_eq!((a.id, a.owner, a.capture), (b.id, b.owner, b.capture));
+            assert!((a.x - b.x).abs() <= POS_TOLERANCE && (a.radius - b.radius).abs() <= POS_TOLERANCE);
+        }
+    }
+
+    #[test]
+    fn positions_clamp_to_map_bounds() {
+        let f = format();
+        let mut snapshot = sim_with_players().update(0.0, &HashMap::new());
+        snapshot.entities[0].transform.x = 5000.0;
+        snapshot.entities[0].transform.y = -5000.0;
+
+        let bytes = encode_envelope(&f, &envelope(Some(snapshot), None));
+        let got = decode_envelope(&f, &bytes).unwrap().snapshot.unwrap();
+        let bounds = world_bounds();
+        assert!((got.entities[0].transform.x - (bounds.min_x + u16::MAX as f32 / CM)).abs() < 0.01);
+        assert!((got.entities[0].transform.y - bounds.min_y).abs() < 0.01);
+    }
+
+    #[test]
+    fn delta_round_trip() {
+        let f = format();
+        let mut sim = sim_with_players();
+        let base = sim.update(0.0, &HashMap::new());
+        let mut current = sim.update(0.05, &HashMap::new());
+        current.entities.last_mut().unwrap().latency = None;
+        current.game_state.team_b_tickets -= 3;
+        let d = delta::diff(&base, &current);
+
+        let bytes = encode_envelope(&f, &envelope(None, Some(d.clone())));
+        let got = decode_envelope(&f, &bytes).unwrap();
+        assert!(got.snapshot.is_none());
+        let got = got.delta.unwrap();
+        assert_eq!(
+            (got.baseline, got.tick, got.removed.len(), got.spawned.len(), got.changed.len()),
+            (d.baseline, d.tick, d.removed.len(), d.spawned.len(), d.changed.len())
+        );
+        for (a, b) in d.changed.iter().zip(&got.changed) {
+            assert_eq!((a.eid, a.cleared, a.transform.is_some()), (b.eid, b.cleared, b.transform.is_some()));
+            assert_eq!((&a.team, &a.score, &a.name), (&b.team, &b.score, &b.name));
+        }
+        assert_eq!(got.game_state, d.game_state);
+        assert_eq!(got.scoreboard, d.scoreboard);
+    }
+
+    #[test]
+    fn garbage_is_rejected() {
+        let f = format();
+        assert!(decode_envelope(&f, &[]).is_err());
+        assert!(decode_envelope(&f, &[HAS_SNAPSHOT, 1, 0xFF]).is_err());
+        assert!(decode_envelope(&f, &[0, 1, 0]).is_err());
+    }
+
+    #[test]
+    fn oversized_maps_are_not_compact() {
+        let mut bounds = world_bounds();
+        bounds.max_x = bounds.min_x + 1000.0;
+        assert!(format_for(&bounds).is_none());
+    }
+}
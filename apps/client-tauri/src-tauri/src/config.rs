@@ -0,0 +1,438 @@
+// apps/client-tauri/src-tauri/src/config.rs
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+use crate::network::NetConfig;
+use crate::relevance::RelevanceConfig;
+use crate::systems::movement::MovementAuthority;
+
+// Picked up from the working directory when no --config is given.
+pub const DEFAULT_CONFIG_FILE: &str = "bf42lite.toml";
+
+// Game modes the sim knows how to run.
+pub const MODES: &[&str] = &["conquest"];
+
+/// Everything a hosted server can be configured with. Loaded from a TOML
+/// file, then command line overrides, then validated as a whole.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub server: ServerSettings,
+    #[serde(rename = "match")]
+    pub game: MatchSettings,
+    pub rules: Rules,
+    pub net: NetSettings,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSettings {
+    // Shown to players browsing for games.
+    pub name: String,
+    // 0.0.0.0 accepts LAN peers; 127.0.0.1 only this machine.
+    pub bind: String,
+    // WebSocket port; UDP uses the same one.
+    pub port: u16,
+    // Sim steps per second.
+    pub tick_rate: u32,
+    // Connected players plus disconnected ones still inside their grace period.
+    pub max_players: u32,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            name: "bf42lite server".to_string(),
+            bind: "0.0.0.0".to_string(),
+            port: 8080,
+            tick_rate: 20,
+            max_players: 32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MatchSettings {
+    // One of maps::AVAILABLE.
+    pub map: String,
+    // One of MODES.
+    pub mode: String,
+    // Starting tickets for each team.
+    pub tickets: u32,
+}
+
+impl Default for MatchSettings {
+    fn default() -> Self {
+        Self {
+            map: "warehouse".to_string(),
+            mode: "conquest".to_string(),
+            tickets: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetSettings {
+    // "server" integrates client input; "client" takes reported transforms
+    // and validates them.
+    pub movement: MovementAuthority,
+    // Kick players idle for this many seconds; 0 never kicks.
+    pub idle_kick_secs: u64,
+    // Also serve snapshots over UDP; off keeps everything on the WebSocket.
+    pub udp: bool,
+    // Only send clients what they can see; off sends every entity to everyone.
+    pub relevance: bool,
+}
+
+impl Default for NetSettings {
+    fn default() -> Self {
+        Self {
+            movement: MovementAuthority::Server,
+            idle_kick_secs: 300,
+            udp: true,
+            relevance: true,
+        }
+    }
+}
+
+/// Gameplay tunables read by the systems every tick.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    // Teammates can hurt each other with bullets, explosions and vehicle weapons.
+    pub friendly_fire: bool,
+    // Rifle damage per hit.
+    pub damage_per_hit: f32,
+    // Seconds between rifle shots.
+    pub fire_interval: f32,
+    // Rifle bullets travel this far before they stop counting.
+    pub hitscan_range: f32,
+    // Seconds a dead soldier waits before respawning.
+    pub respawn_time: f32,
+    // Flag capture progress per second (a full capture is 1.0).
+    pub capture_speed: f32,
+    // Progress lost per second on an empty or contested flag.
+    pub decay_speed: f32,
+    // Tickets per second the losing team bleeds for each flag it is behind.
+    pub ticket_bleed_per_flag: f32,
+    // Landing slower than this is free. A normal jump lands at ~9 u/s.
+    pub safe_fall_speed: f32,
+    // Damage per unit of impact speed above safe_fall_speed.
+    pub fall_damage_per_speed: f32,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            friendly_fire: true,
+            damage_per_hit: 34.0,
+            fire_interval: 0.15,
+            hitscan_range: 1000.0,
+            respawn_time: 5.0,
+            capture_speed: 0.25,
+            decay_speed: 0.10,
+            ticket_bleed_per_flag: 0.5,
+            safe_fall_speed: 12.0,
+            fall_damage_per_speed: 5.0,
+        }
+    }
+}
+
+// Shorthand options and the config key each one sets.
+const FLAGS: &[(&str, &str)] = &[
+    ("--name", "server.name"),
+    ("--bind", "server.bind"),
+    ("--port", "server.port"),
+    ("--tick-rate", "server.tick_rate"),
+    ("--max-players", "server.max_players"),
+    ("--map", "match.map"),
+    ("--mode", "match.mode"),
+    ("--tickets", "match.tickets"),
+    ("--friendly-fire", "rules.friendly_fire"),
+];
+
+// Text settings. Command line values for these are taken as they are, so a
+// server called "2024" doesn't turn into a number.
+const STRING_KEYS: &[&str] = &["server.name", "server.bind", "match.map", "match.mode"];
+
+pub const USAGE: &str = "\
+options:
+  --config <file>        TOML config file (default: ./bf42lite.toml if present)
+  --host                 host at launch instead of waiting for the menu
+  --name <text>          server name
+  --bind <ip>            address to listen on
+  --port <n>             WebSocket/UDP port
+  --tick-rate <hz>       sim steps per second
+  --max-players <n>      player limit
+  --map <name>           map to host
+  --mode <name>          game mode
+  --tickets <n>          starting tickets per team
+  --friendly-fire <bool> teammates can hurt each other
+  --set <key>=<value>    any other setting, e.g. --set rules.damage_per_hit=40";
+
+impl ServerConfig {
+    /// Load the config named on the command line (or the default file),
+    /// apply the remaining options and validate the result.
+    /// `args` excludes the program name.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut file: Option<PathBuf> = None;
+        let mut overrides: Vec<(String, String)> = Vec::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // Only our own --options; the OS and Tauri pass other things too.
+            if !arg.starts_with("--") {
+                continue;
+            }
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{} needs a value\n{}", flag, USAGE))
+            };
+            match flag.as_str() {
+                "--config" => file = Some(PathBuf::from(value()?)),
+                // Read by lib.rs: host at launch.
+                "--host" => {}
+                "--set" => {
+                    let setting = value()?;
+                    let (key, raw) = setting
+                        .split_once('=')
+                        .ok_or_else(|| format!("--set expects key=value, got {:?}", setting))?;
+                    overrides.push((key.trim().to_string(), raw.trim().to_string()));
+                }
+                _ => {
+                    let Some((_, key)) = FLAGS.iter().find(|(f, _)| *f == flag) else {
+                        return Err(format!("unknown option {}\n{}", flag, USAGE));
+                    };
+                    overrides.push((key.to_string(), value()?));
+                }
+            }
+        }
+
+        let mut table = match file {
+            Some(path) => read_table(&path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => read_table(Path::new(DEFAULT_CONFIG_FILE))?,
+            None => Table::new(),
+        };
+        for (key, raw) in &overrides {
+            set(&mut table, key, raw)?;
+        }
+        Self::from_table(table)
+    }
+
+    /// Parse and validate a whole config from TOML text.
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let table: Table = toml::from_str(text).map_err(|e| format!("invalid config: {}", e))?;
+        Self::from_table(table)
+    }
+
+    fn from_table(table: Table) -> Result<Self, String> {
+        let config: ServerConfig = Value::Table(table)
+            .try_into()
+            .map_err(|e| format!("invalid config: {}", e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check every setting, reporting all problems at once.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors: Vec<String> = Vec::new();
+        let mut check = |ok: bool, problem: &str| {
+            if !ok {
+                errors.push(problem.to_string());
+            }
+        };
+
+        let s = &self.server;
+        check(!s.name.trim().is_empty(), "server.name must not be empty");
+        check(s.bind.parse::<IpAddr>().is_ok(), &format!("server.bind {:?} is not an IP address", s.bind));
+        check(s.port != 0, "server.port must not be 0");
+        check((1..=128).contains(&s.tick_rate), "server.tick_rate must be between 1 and 128");
+        check((1..=64).contains(&s.max_players), "server.max_players must be between 1 and 64");
+
+        let m = &self.game;
+        check(
+            crate::maps::AVAILABLE.contains(&m.map.as_str()),
+            &format!("match.map {:?} is not one of {:?}", m.map, crate::maps::AVAILABLE),
+        );
+        check(
+            MODES.contains(&m.mode.as_str()),
+            &format!("match.mode {:?} is not one of {:?}", m.mode, MODES),
+        );
+        check((1..=10_000).contains(&m.tickets), "match.tickets must be between 1 and 10000");
+
+        let r = &self.rules;
+        for (name, value) in [
+            ("damage_per_hit", r.damage_per_hit),
+            ("respawn_time", r.respawn_time),
+            ("decay_speed", r.decay_speed),
+            ("ticket_bleed_per_flag", r.ticket_bleed_per_flag),
+            ("safe_fall_speed", r.safe_fall_speed),
+            ("fall_damage_per_speed", r.fall_damage_per_speed),
+        ] {
+            check(value.is_finite() && value >= 0.0, &format!("rules.{} must be 0 or more", name));
+        }
+        for (name, value) in [
+            ("fire_interval", r.fire_interval),
+            ("hitscan_range", r.hitscan_range),
+            ("capture_speed", r.capture_speed),
+        ] {
+            check(value.is_finite() && value > 0.0, &format!("rules.{} must be more than 0", name));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("invalid config: {}", errors.join("; ")))
+        }
+    }
+
+    /// Address the WebSocket (and UDP) server listens on.
+    pub fn addr(&self) -> String {
+        match self.server.bind.parse::<IpAddr>() {
+            Ok(ip) => SocketAddr::new(ip, self.server.port).to_string(),
+            Err(_) => format!("{}:{}", self.server.bind, self.server.port),
+        }
+    }
+
+    pub fn tick(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.server.tick_rate.max(1) as f64)
+    }
+
+    /// Network layer settings; anything without a config key keeps its default.
+    pub fn net_config(&self) -> NetConfig {
+        let net = &self.net;
+        NetConfig {
+            idle_kick: (net.idle_kick_secs > 0).then(|| Duration::from_secs(net.idle_kick_secs)),
+            udp: net.udp,
+            relevance: net.relevance.then(RelevanceConfig::default),
+            ..NetConfig::default()
+        }
+    }
+}
+
+fn read_table(path: &Path) -> Result<Table, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("can't read config {}: {}", path.display(), e))?;
+    toml::from_str(&text).map_err(|e| format!("invalid config {}: {}", path.display(), e))
+}
+
+/// Set a dotted `section.key` from a command line value. Values of text
+/// settings are kept as strings; others are read as TOML (numbers, booleans)
+/// and fall back to plain strings.
+fn set(table: &mut Table, key: &str, raw: &str) -> Result<(), String> {
+    let Some((section, field)) = key.split_once('.') else {
+        return Err(format!("setting {:?} should look like section.key", key));
+    };
+    let value = if STRING_KEYS.contains(&key) {
+        Value::String(raw.to_string())
+    } else {
+        toml::from_str::<Table>(&format!("v = {}", raw))
+            .ok()
+            .and_then(|mut t| t.remove("v"))
+            .unwrap_or_else(|| Value::String(raw.to_string()))
+    };
+    let section = table
+        .entry(section.to_string())
+        .or_insert_with(|| Value::Table(Table::new()))
+        .as_table_mut()
+        .ok_or_else(|| format!("config key {:?} is not a section", section))?;
+    section.insert(field.to_string(), value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn file_values_and_overrides_combine() {
+        let path = std::env::temp_dir().join(format!("bf42lite-config-{}.toml", std::process::id()));
+        std::fs::write(&path, "[server]\nport = 9000\n\n[match]\ntickets = 250\n").unwrap();
+
+        let config = ServerConfig::from_args(args(&format!(
+            "--config {} --tickets 300 --friendly-fire=false --bind 192.168.1.10 --set rules.damage_per_hit=40",
+            path.display()
+        )))
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.server.port, 9000);
+        assert_eq!(config.game.tickets, 300);
+        assert!(!config.rules.friendly_fire);
+        assert_eq!(config.rules.damage_per_hit, 40.0);
+        assert_eq!(config.addr(), "192.168.1.10:9000");
+        assert_eq!(config.server.tick_rate, ServerSettings::default().tick_rate);
+    }
+
+    #[test]
+    fn net_settings_reach_the_network_layer() {
+        let defaults = ServerConfig::default().net_config();
+        assert_eq!(defaults.idle_kick, NetConfig::default().idle_kick);
+        assert!(defaults.udp && defaults.relevance.is_some());
+
+        let config = ServerConfig::from_args(args(
+            "--set net.movement=client --set net.idle_kick_secs=0 --set net.udp=false --set net.relevance=false",
+        ))
+        .unwrap();
+        assert_eq!(config.net.movement, MovementAuthority::Client);
+        let net = config.net_config();
+        assert_eq!(net.idle_kick, None);
+        assert!(!net.udp && net.relevance.is_none());
+
+        let err = ServerConfig::from_args(args("--set net.movement=both")).unwrap_err();
+        assert!(err.contains("movement"), "{}", err);
+    }
+
+    #[test]
+    fn text_settings_stay_text() {
+        let config = ServerConfig::from_args(args("--name 2024 --set server.name=true")).unwrap();
+        assert_eq!(config.server.name, "true");
+        let config = ServerConfig::from_args(args("--name 2024")).unwrap();
+        assert_eq!(config.server.name, "2024");
+
+        // Still a clear complaint about the map, not about its type.
+        let err = ServerConfig::from_args(args("--map 1")).unwrap_err();
+        assert!(err.contains("match.map \"1\" is not one of"), "{}", err);
+        // Other settings are still typed.
+        assert!(ServerConfig::from_args(args("--port eighty")).unwrap_err().contains("port"));
+    }
+
+    #[test]
+    fn example_config_matches_defaults() {
+        let example = ServerConfig::from_toml(include_str!("../bf42lite.example.toml")).unwrap();
+        assert_eq!(example, ServerConfig::default());
+    }
+
+    #[test]
+    fn mistakes_are_reported_clearly() {
+        assert!(ServerConfig::default().validate().is_ok());
+
+        let err = ServerConfig::from_toml("[server]\ntikc_rate = 30\n").unwrap_err();
+        assert!(err.contains("tikc_rate"), "{}", err);
+
+        let err = ServerConfig::from_toml("[server]\nport = \"eighty\"\n").unwrap_err();
+        assert!(err.contains("port"), "{}", err);
+
+        let err = ServerConfig::from_toml("[server]\ntick_rate = 0\n[match]\nmap = \"nowhere\"\n").unwrap_err();
+        assert!(err.contains("server.tick_rate") && err.contains("match.map"), "{}", err);
+
+        assert!(ServerConfig::from_args(args("--tickrate 30")).unwrap_err().contains("unknown option"));
+        assert!(ServerConfig::from_args(args("--port")).unwrap_err().contains("needs a value"));
+    }
+}
@@ -0,0 +1,263 @@
+// apps/client-tauri/src-tauri/src/delta.rs
+use std::collections::HashMap;
+
+use crate::protocol::{
+    EntityDelta, EntitySnapshot, SnapshotDelta, TickSnapshot, CLEARED_HEALTH, CLEARED_LATENCY,
+    CLEARED_LOADOUT, CLEARED_NAME, CLEARED_SCORE, CLEARED_STAMINA, CLEARED_TEAM,
+};
+
+/// Changes that turn `base` into `current`.
+pub fn diff(base: &TickSnapshot, current: &TickSnapshot) -> SnapshotDelta {
+    let mut delta = SnapshotDelta {
+        baseline: base.tick,
+        tick: current.tick,
+        ..Default::default()
+    };
+
+    let base_entities: HashMap<u32, &EntitySnapshot> =
+        base.entities.iter().map(|e| (e.eid, e)).collect();
+    for e in &current.entities {
+        match base_entities.get(&e.eid) {
+            Some(old) => delta.changed.extend(diff_entity(old, e)),
+            None => delta.spawned.push(e.clone()),
+        }
+    }
+    delta.removed = removed_ids(&base.entities, &current.entities, |e| e.eid);
+
+    delta.flags = changed_items(&base.flags, &current.flags, |f| f.id);
+
+    if base.game_state != current.game_state {
+        delta.game_state = Some(current.game_state.clone());
+    }
+
+    delta.projectiles = changed_items(&base.projectiles, &current.projectiles, |p| p.id);
+    delta.removed_projectiles = removed_ids(&base.projectiles, &current.projectiles, |p| p.id);
+    delta.vehicles = changed_items(&base.vehicles, &current.vehicles, |v| v.id);
+    delta.removed_vehicles = removed_ids(&base.vehicles, &current.vehicles, |v| v.id);
+
+    if base.scoreboard != current.scoreboard {
+        delta.scoreboard = Some(current.scoreboard.clone());
+    }
+    delta
+}
+
+/// Rebuild the snapshot a delta was made from. Fails if `base` is not the
+/// delta's baseline or the delta refers to entities the baseline lacks.
+// Client side of the protocol; the host itself only diffs.
+#[allow(dead_code)]
+pub fn apply(base: &TickSnapshot, delta: &SnapshotDelta) -> Result<TickSnapshot, String> {
+    if base.tick != delta.baseline {
+        return Err(format!(
+            "delta is against tick {}, baseline is tick {}",
+            delta.baseline, base.tick
+        ));
+    }
+
+    let mut entities = base.entities.clone();
+    entities.retain(|e| !delta.removed.contains(&e.eid));
+    for change in &delta.changed {
+        let entity = entities
+            .iter_mut()
+            .find(|e| e.eid == change.eid)
+            .ok_or_else(|| format!("delta changes unknown entity {}", change.eid))?;
+        apply_entity(entity, change);
+    }
+    entities.extend(delta.spawned.iter().cloned());
+    entities.sort_by_key(|e| e.eid);
+
+    let mut flags = base.flags.clone();
+    for flag in &delta.flags {
+        match flags.iter_mut().find(|f| f.id == flag.id) {
+            Some(f) => *f = flag.clone(),
+            None => flags.push(flag.clone()),
+        }
+    }
+
+    let mut projectiles = base.projectiles.clone();
+    projectiles.retain(|p| !delta.removed_projectiles.contains(&p.id));
+    upsert(&mut projectiles, &delta.projectiles, |p| p.id);
+
+    let mut vehicles = base.vehicles.clone();
+    vehicles.retain(|v| !delta.removed_vehicles.contains(&v.id));
+    upsert(&mut vehicles, &delta.vehicles, |v| v.id);
+
+    Ok(TickSnapshot {
+        entities,
+        flags,
+        game_state: delta.game_state.clone().unwrap_or_else(|| base.game_state.clone()),
+        projectiles,
+        vehicles,
+        scoreboard: delta.scoreboard.clone().unwrap_or_else(|| base.scoreboard.clone()),
+        tick: delta.tick,
+    })
+}
+
+fn diff_entity(old: &EntitySnapshot, new: &EntitySnapshot) -> Option<EntityDelta> {
+    let mut d = EntityDelta {
+        eid: new.eid,
+        ..Default::default()
+    };
+    if old.transform != new.transform {
+        d.transform = Some(new.transform);
+    }
+    if old.kind != new.kind {
+        d.kind = Some(new.kind);
+    }
+    diff_component(&old.health, &new.health, &mut d.health, &mut d.cleared, CLEARED_HEALTH);
+    diff_component(&old.stamina, &new.stamina, &mut d.stamina, &mut d.cleared, CLEARED_STAMINA);
+    diff_component(&old.team, &new.team, &mut d.team, &mut d.cleared, CLEARED_TEAM);
+    diff_component(&old.score, &new.score, &mut d.score, &mut d.cleared, CLEARED_SCORE);
+    diff_component(&old.loadout, &new.loadout, &mut d.loadout, &mut d.cleared, CLEARED_LOADOUT);
+    diff_component(&old.name, &new.name, &mut d.name, &mut d.cleared, CLEARED_NAME);
+    diff_component(&old.latency, &new.latency, &mut d.latency, &mut d.cleared, CLEARED_LATENCY);
+
+    let unchanged = EntityDelta {
+        eid: new.eid,
+        ..Default::default()
+    };
+    (d != unchanged).then_some(d)
+}
+
+fn apply_entity(e: &mut EntitySnapshot, d: &EntityDelta) {
+    if let Some(t) = d.transform {
+        e.transform = t;
+    }
+    if let Some(kind) = d.kind {
+        e.kind = kind;
+    }
+    apply_component(&mut e.health, &d.health, d.cleared, CLEARED_HEALTH);
+    apply_component(&mut e.stamina, &d.stamina, d.cleared, CLEARED_STAMINA);
+    apply_component(&mut e.team, &d.team, d.cleared, CLEARED_TEAM);
+    apply_component(&mut e.score, &d.score, d.cleared, CLEARED_SCORE);
+    apply_component(&mut e.loadout, &d.loadout, d.cleared, CLEARED_LOADOUT);
+    apply_component(&mut e.name, &d.name, d.cleared, CLEARED_NAME);
+    apply_component(&mut e.latency, &d.latency, d.cleared, CLEARED_LATENCY);
+}
+
+fn diff_component<T: PartialEq + Clone>(
+    old: &Option<T>,
+    new: &Option<T>,
+    out: &mut Option<T>,
+    cleared: &mut u16,
+    bit: u16,
+) {
+    if old == new {
+        return;
+    }
+    match new {
+        Some(v) => *out = Some(v.clone()),
+        None => *cleared |= bit,
+    }
+}
+
+fn apply_component<T: Clone>(field: &mut Option<T>, change: &Option<T>, cleared: u16, bit: u16) {
+    if cleared & bit != 0 {
+        *field = None;
+    } else if let Some(v) = change {
+        *field = Some(v.clone());
+    }
+}
+
+/// Items in `current` that are new or differ from the item with the same id in `base`.
+fn changed_items<T: PartialEq + Clone>(base: &[T], current: &[T], id: impl Fn(&T) -> u32) -> Vec<T> {
+    let base: HashMap<u32, &T> = base.iter().map(|item| (id(item), item)).collect();
+    current
+        .iter()
+        .filter(|item| base.get(&id(item)).is_none_or(|old| *old != *item))
+        .cloned()
+        .collect()
+}
+
+fn removed_ids<T>(base: &[T], current: &[T], id: impl Fn(&T) -> u32) -> Vec<u32> {
+    base.iter()
+        .map(&id)
+        .filter(|old| !current.iter().any(|item| id(item) == *old))
+        .collect()
+}
+
+// Snapshots list projectiles and vehicles by id.
+fn upsert<T: Clone>(items: &mut Vec<T>, changes: &[T], id: impl Fn(&T) -> u32) {
+    for change in changes {
+        match items.iter_mut().find(|item| id(item) == id(change)) {
+            Some(item) => *item = change.clone(),
+            None => items.push(change.clone()),
+        }
+    }
+    items.sort_by_key(|item| id(item));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::SimState;
+
+    fn join(sim: &mut SimState, name: &str) -> u32 {
+        let conn = sim.allocate_connection_id();
+        sim.handle_join(conn, name, None).expect("join failed");
+        conn
+    }
+
+    #[test]
+    fn unchanged_snapshot_gives_empty_delta() {
+        let mut sim = SimState::new();
+        join(&mut sim, "a");
+        let base = sim.update(0.0, &HashMap::new());
+        let mut next = base.clone();
+        next.tick += 1;
+
+        let delta = diff(&base, &next);
+        assert_eq!(
+            delta,
+            SnapshotDelta { baseline: base.tick, tick: next.tick, ..Default::default() }
+        );
+        assert_eq!(apply(&base, &delta).unwrap(), next);
+    }
+
+    #[test]
+    fn round_trip_through_joins_leaves_and_changes() {
+        let mut sim = SimState::new();
+        let a = join(&mut sim, "a");
+        join(&mut sim, "b");
+        let base = sim.update(0.05, &HashMap::new());
+
+        sim.handle_disconnect(a, crate::session::DisconnectReason::ClientClosed);
+        join(&mut sim, "c");
+        let mut current = sim.update(0.05, &HashMap::new());
+        // A component going away has to survive the round trip too.
+        let player = current.entities.iter_mut().find(|e| e.latency.is_some()).unwrap();
+        player.latency = None;
+        current.game_state.team_a_tickets -= 1;
+
+        let delta = diff(&base, &current);
+        assert_eq!(delta.removed.len(), 1);
+        assert_eq!(delta.spawned.len(), 1);
+        assert!(delta.game_state.is_some());
+        assert_eq!(apply(&base, &delta).unwrap(), current);
+    }
+
+    #[test]
+    fn delta_only_carries_changed_fields() {
+        let mut sim = SimState::new();
+        join(&mut sim, "a");
+        let base = sim.update(0.0, &HashMap::new());
+        let mut current = base.clone();
+        current.tick += 1;
+        current.entities.last_mut().unwrap().transform.x += 1.0;
+
+        let delta = diff(&base, &current);
+        assert_eq!(delta.changed.len(), 1);
+        let change = &delta.changed[0];
+        assert!(change.transform.is_some());
+        assert!(change.health.is_none() && change.score.is_none() && change.name.is_none());
+        assert!(delta.flags.is_empty() && delta.vehicles.is_empty());
+    }
+
+    #[test]
+    fn wrong_baseline_is_rejected() {
+        let mut sim = SimState::new();
+        let base = sim.update(0.0, &HashMap::new());
+        let current = sim.update(0.0, &HashMap::new());
+        let delta = diff(&base, &current);
+        assert!(apply(&current, &delta).is_err());
+    }
+}
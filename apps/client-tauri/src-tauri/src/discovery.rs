@@ -0,0 +1,251 @@
+// apps/client-tauri/src-tauri/src/discovery.rs
+
+// LAN server discovery. Hosts listen on DISCOVERY_PORT; a client broadcasts
+// QUERY there and every host answers with REPLY followed by a JSON
+// `ServerInfo`. The game port is part of the reply, so hosts can run on any
+// port while discovery stays on a fixed one.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::net::UdpSocket;
+use tracing::{debug, info, warn};
+
+use crate::config::ServerConfig;
+use crate::protocol::PROTOCOL_VERSION;
+use crate::sim_loop::SimHandle;
+
+pub const DISCOVERY_PORT: u16 = 27042;
+pub const QUERY: &[u8] = b"BF42?";
+pub const REPLY: &[u8] = b"BF42!";
+// How long `query` collects replies when the caller doesn't say.
+pub const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_millis(500);
+// At most one reply per source address this often, so spoofed queries can't
+// turn the host into a traffic amplifier.
+const REPLY_INTERVAL: Duration = Duration::from_millis(250);
+// Sources remembered for the rate limit; queries beyond this are dropped.
+const MAX_SOURCES: usize = 256;
+
+/// What a host tells LAN clients about itself.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerInfo {
+    pub name: String,
+    pub map: String,
+    pub mode: String,
+    pub players: u32,
+    pub max_players: u32,
+    pub protocol_version: u32,
+    // WebSocket (and UDP) game port on the replying host.
+    pub port: u16,
+}
+
+/// A server found on the local network.
+#[derive(Debug, Clone, Serialize)]
+pub struct LanServer {
+    // Address to connect to: the replying host's IP and its game port.
+    pub addr: String,
+    pub info: ServerInfo,
+    pub ping_ms: u32,
+}
+
+/// Where discovery listens. A server bound to loopback only answers this
+/// machine; anything else answers the whole LAN.
+fn listen_addr(config: &ServerConfig, port: u16) -> SocketAddr {
+    match config.server.bind.parse::<IpAddr>() {
+        Ok(ip) if ip.is_loopback() => SocketAddr::new(ip, port),
+        _ => SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)),
+    }
+}
+
+/// Answer discovery queries until the sim stops. Fails only if the port
+/// can't be bound, e.g. another host already runs on this machine.
+pub async fn serve(port: u16, config: &ServerConfig, sim: SimHandle) -> Result<(), String> {
+    let addr = listen_addr(config, port);
+    let socket = UdpSocket::bind(addr)
+        .await
+        .map_err(|e| format!("discovery: can't bind UDP {}: {}", addr, e))?;
+    info!(target: "net", %addr, "answering LAN discovery queries");
+
+    let mut replied: HashMap<IpAddr, Instant> = HashMap::new();
+    let mut buf = [0u8; 64];
+    loop {
+        let (len, from) = match socket.recv_from(&mut buf).await {
+            Ok(r) => r,
+            Err(e) => {
+                // Windows reports ICMP port unreachable from earlier sends here.
+                debug!(target: "net", error = %e, "discovery receive failed");
+                continue;
+            }
+        };
+        if &buf[..len] != QUERY {
+            continue;
+        }
+        let now = Instant::now();
+        let recent = |at: &Instant| now.duration_since(*at) < REPLY_INTERVAL;
+        if replied.get(&from.ip()).is_some_and(recent) {
+            continue;
+        }
+        if replied.len() >= MAX_SOURCES {
+            replied.retain(|_, at| recent(at));
+            if replied.len() >= MAX_SOURCES {
+                debug!(target: "net", %from, "discovery busy, query dropped");
+                continue;
+            }
+        }
+        replied.insert(from.ip(), now);
+        let Some(players) = sim.player_count().await else {
+            return Ok(());
+        };
+        let info = ServerInfo {
+            name: config.server.name.clone(),
+            map: config.game.map.clone(),
+            mode: config.game.mode.clone(),
+            players: players as u32,
+            max_players: config.server.max_players,
+            protocol_version: PROTOCOL_VERSION,
+            port: config.server.port,
+        };
+        let mut reply = REPLY.to_vec();
+        reply.extend(serde_json::to_vec(&info).map_err(|e| e.to_string())?);
+        if let Err(e) = socket.send_to(&reply, from).await {
+            warn!(target: "net", %from, error = %e, "discovery reply failed");
+        }
+    }
+}
+
+/// Broadcast a query on the local network and list the servers that answer
+/// within `timeout`.
+pub async fn query(timeout: Duration) -> Result<Vec<LanServer>, String> {
+    let targets = [
+        SocketAddr::from((Ipv4Addr::BROADCAST, DISCOVERY_PORT)),
+        // Broadcasts don't always loop back; a host on this machine still counts.
+        SocketAddr::from((Ipv4Addr::LOCALHOST, DISCOVERY_PORT)),
+    ];
+    query_at(&targets, timeout).await
+}
+
+/// Send a query to each target and collect replies, one per server.
+pub async fn query_at(targets: &[SocketAddr], timeout: Duration) -> Result<Vec<LanServer>, String> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+        .await
+        .map_err(|e| format!("discovery: {}", e))?;
+    socket.set_broadcast(true).map_err(|e| format!("discovery: {}", e))?;
+
+    let sent = Instant::now();
+    let mut reached = false;
+    for target in targets {
+        match socket.send_to(QUERY, target).await {
+            Ok(_) => reached = true,
+            Err(e) => debug!(target: "net", %target, error = %e, "discovery query failed"),
+        }
+    }
+    if !reached {
+        return Err("discovery: couldn't send a query on any interface".to_string());
+    }
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut found: HashMap<String, LanServer> = HashMap::new();
+    let mut buf = [0u8; 1024];
+    loop {
+        let (len, from) = match tokio::time::timeout_at(deadline, socket.recv_from(&mut buf)).await {
+            Ok(Ok(r)) => r,
+            Ok(Err(e)) => {
+                debug!(target: "net", error = %e, "discovery receive failed");
+                continue;
+            }
+            Err(_) => break,
+        };
+        let Some(body) = buf[..len].strip_prefix(REPLY) else { continue };
+        let Ok(info) = serde_json::from_slice::<ServerInfo>(body) else {
+            debug!(target: "net", %from, "ignoring malformed discovery reply");
+            continue;
+        };
+        let addr = SocketAddr::new(from.ip(), info.port).to_string();
+        // The same host can answer both the broadcast and the loopback query.
+        found.entry(addr.clone()).or_insert(LanServer {
+            addr,
+            info,
+            ping_ms: sent.elapsed().as_millis() as u32,
+        });
+    }
+
+    let mut servers: Vec<LanServer> = found.into_values().collect();
+    servers.sort_by_key(|s| s.ping_ms);
+    Ok(servers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::SimState;
+    use crate::sim_loop::{self, JoinRequest};
+
+    #[tokio::test]
+    async fn hosts_answer_queries_with_live_player_counts() {
+        let mut config = ServerConfig::default();
+        config.server.name = "test host".to_string();
+        config.server.port = 9123;
+        let sim = sim_loop::spawn(SimState::with_config(&config), Duration::from_millis(10));
+        sim.join(JoinRequest {
+            name: "alice".to_string(),
+            requested_team: None,
+            resume_token: None,
+            compact: false,
+        })
+        .await
+        .unwrap();
+
+        // Pick a free port for the responder rather than the shared one.
+        let port = std::net::UdpSocket::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let responder = tokio::spawn({
+            let sim = sim.clone();
+            async move { serve(port, &config, sim).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let target = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+        let servers = query_at(&[target, target], Duration::from_millis(300)).await.unwrap();
+        responder.abort();
+
+        assert_eq!(servers.len(), 1);
+        let server = &servers[0];
+        assert_eq!(server.addr, "127.0.0.1:9123");
+        assert_eq!(server.info.name, "test host");
+        assert_eq!(server.info.players, 1);
+        assert_eq!(server.info.max_players, 32);
+        assert_eq!(server.info.protocol_version, PROTOCOL_VERSION);
+    }
+
+    #[tokio::test]
+    async fn replies_are_rate_limited_per_source() {
+        let mut config = ServerConfig::default();
+        config.server.bind = "127.0.0.1".to_string();
+        let sim = sim_loop::spawn(SimState::with_config(&config), Duration::from_millis(10));
+        let port = std::net::UdpSocket::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        assert_eq!(listen_addr(&config, port), SocketAddr::from((Ipv4Addr::LOCALHOST, port)));
+        let responder = tokio::spawn(async move { serve(port, &config, sim).await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let target = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+        async fn replies(client: &UdpSocket) -> usize {
+            let mut buf = [0u8; 1024];
+            let mut n = 0;
+            while tokio::time::timeout(Duration::from_millis(100), client.recv_from(&mut buf)).await.is_ok() {
+                n += 1;
+            }
+            n
+        }
+
+        for _ in 0..5 {
+            client.send_to(QUERY, target).await.unwrap();
+        }
+        assert_eq!(replies(&client).await, 1);
+        tokio::time::sleep(REPLY_INTERVAL).await;
+        client.send_to(QUERY, target).await.unwrap();
+        assert_eq!(replies(&client).await, 1);
+        responder.abort();
+    }
+}
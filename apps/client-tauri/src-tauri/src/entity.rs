@@ -0,0 +1,127 @@
+// apps/client-tauri/src-tauri/src/entity.rs
+use std::collections::{BTreeMap, HashMap};
+
+use crate::player::Player;
+use crate::projectile::Projectile;
+use crate::protocol::{EntityKind, TeamId, Transform};
+use crate::vehicle::Vehicle;
+
+/// Id of anything that lives in the world. Allocated by the store, never
+/// reused, and unrelated to network connection ids.
+pub type EntityId = u32;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn full(max: f32) -> Self {
+        Self { current: max, max }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Score {
+    pub kills: u32,
+    pub deaths: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Loadout {
+    pub class_id: u8,
+}
+
+/// Every entity in the sim, stored as one map per component.
+/// Shared components (transform, health, team, score, loadout) are optional
+/// per entity; `players`, `vehicles` and `projectiles` hold the state that
+/// only makes sense for that kind.
+pub struct EntityStore {
+    next_id: EntityId,
+    // Live entities. Ordered so snapshots list them in a stable order.
+    pub kinds: BTreeMap<EntityId, EntityKind>,
+
+    pub transforms: HashMap<EntityId, Transform>,
+    pub healths: HashMap<EntityId, Health>,
+    pub teams: HashMap<EntityId, TeamId>,
+    pub scores: HashMap<EntityId, Score>,
+    pub loadouts: HashMap<EntityId, Loadout>,
+    pub names: HashMap<EntityId, String>,
+
+    pub players: HashMap<EntityId, Player>,
+    pub vehicles: HashMap<EntityId, Vehicle>,
+    pub projectiles: HashMap<EntityId, Projectile>,
+}
+
+impl EntityStore {
+    pub fn new() -> Self {
+        Self {
+            next_id: 1,
+            kinds: BTreeMap::new(),
+            transforms: HashMap::new(),
+            healths: HashMap::new(),
+            teams: HashMap::new(),
+            scores: HashMap::new(),
+            loadouts: HashMap::new(),
+            names: HashMap::new(),
+            players: HashMap::new(),
+            vehicles: HashMap::new(),
+            projectiles: HashMap::new(),
+        }
+    }
+
+    /// Allocate a new entity with no components.
+    pub fn spawn(&mut self, kind: EntityKind) -> EntityId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.kinds.insert(id, kind);
+        id
+    }
+
+    /// Bring back a despawned id with no components, so a returning client
+    /// keeps its entity id. Fails if the id is live or was never handed out.
+    pub fn restore(&mut self, id: EntityId, kind: EntityKind) -> bool {
+        if id == 0 || id >= self.next_id || self.kinds.contains_key(&id) {
+            return false;
+        }
+        self.kinds.insert(id, kind);
+        true
+    }
+
+    /// Remove an entity and all of its components.
+    pub fn despawn(&mut self, id: EntityId) {
+        self.kinds.remove(&id);
+        self.transforms.remove(&id);
+        self.healths.remove(&id);
+        self.teams.remove(&id);
+        self.scores.remove(&id);
+        self.loadouts.remove(&id);
+        self.names.remove(&id);
+        self.players.remove(&id);
+        self.vehicles.remove(&id);
+        self.projectiles.remove(&id);
+    }
+
+    /// Subtract health and return what is left, or None if the entity has no health.
+    pub fn damage(&mut self, id: EntityId, amount: f32) -> Option<f32> {
+        let health = self.healths.get_mut(&id)?;
+        health.current -= amount;
+        Some(health.current)
+    }
+
+    /// Two different players on the same team.
+    pub fn teammates(&self, a: EntityId, b: EntityId) -> bool {
+        a != b
+            && matches!(
+                (self.teams.get(&a), self.teams.get(&b)),
+                (Some(x), Some(y)) if x == y && *x != TeamId::None
+            )
+    }
+
+    pub fn award_kill(&mut self, id: EntityId) {
+        if let Some(score) = self.scores.get_mut(&id) {
+            score.kills += 1;
+        }
+    }
+}
@@ -0,0 +1,107 @@
+// apps/client-tauri/src-tauri/src/events.rs
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+
+use crate::entity::EntityId;
+use crate::protocol::{GameModeState, TeamId};
+
+/// What the host dashboard hears about, straight from the sim thread.
+/// Each variant goes out as its own Tauri event, see `name`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ServerEvent {
+    PlayerJoined {
+        player: EntityId,
+        name: String,
+        team: TeamId,
+        // Reclaimed a session after a dropped connection.
+        resumed: bool,
+    },
+    PlayerLeft {
+        player: EntityId,
+        name: String,
+        reason: String,
+    },
+    MatchPhase {
+        phase: MatchPhase,
+        winner: TeamId,
+        team_a_tickets: i32,
+        team_b_tickets: i32,
+    },
+    // A flag changed owner; TeamId::None means it went neutral.
+    FlagCaptured {
+        flag: u32,
+        owner: TeamId,
+        previous: TeamId,
+    },
+    // Sent about once a second.
+    Stats(ServerStats),
+}
+
+impl ServerEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServerEvent::PlayerJoined { .. } => "player-joined",
+            ServerEvent::PlayerLeft { .. } => "player-left",
+            ServerEvent::MatchPhase { .. } => "match-phase",
+            ServerEvent::FlagCaptured { .. } => "flag-captured",
+            ServerEvent::Stats(_) => "server-stats",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchPhase {
+    // Nobody connected.
+    Waiting,
+    InProgress,
+    // A team ran out of tickets.
+    Ended,
+}
+
+impl MatchPhase {
+    pub fn of(players: usize, game: &GameModeState) -> Self {
+        if game.match_ended {
+            MatchPhase::Ended
+        } else if players == 0 {
+            MatchPhase::Waiting
+        } else {
+            MatchPhase::InProgress
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServerStats {
+    pub ticks: u32,
+    // Time spent stepping the sim, not counting the sleep between ticks.
+    pub tick_avg_ms: f32,
+    pub tick_max_ms: f32,
+    pub players: u32,
+    pub bytes_out_per_sec: u64,
+    pub bytes_in_per_sec: u64,
+}
+
+/// Bytes moved over the WebSocket and UDP, summed over all clients.
+#[derive(Debug, Default)]
+pub struct Traffic {
+    bytes_out: AtomicU64,
+    bytes_in: AtomicU64,
+}
+
+impl Traffic {
+    pub fn sent(&self, bytes: usize) {
+        self.bytes_out.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn received(&self, bytes: usize) {
+        self.bytes_in.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Running totals (out, in) since the server started.
+    pub fn totals(&self) -> (u64, u64) {
+        (self.bytes_out.load(Ordering::Relaxed), self.bytes_in.load(Ordering::Relaxed))
+    }
+}
@@ -0,0 +1,352 @@
+// apps/client-tauri/src-tauri/src/host.rs
+
+// Lifecycle of the embedded server. The menu hosts and stops matches through
+// the Tauri commands in lib.rs, which call into `Host`; started with --host,
+// the app hosts at launch. Every state change is passed to `notify` so the UI
+// can follow along, and the sim's dashboard events go to `publish`.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::config::ServerConfig;
+use crate::discovery::{self, DISCOVERY_PORT};
+use crate::events::ServerEvent;
+use crate::network;
+use crate::sim::SimState;
+use crate::sim_loop::{self, SimHandle};
+
+// Tauri event carrying a `ServerStatus` whenever the server starts or stops.
+pub const STATE_EVENT: &str = "server-state";
+// Time left for the last events (players leaving) to reach the dashboard on stop.
+const EVENT_FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerState {
+    Stopped,
+    Running,
+    // Stopped on its own; `error` says why.
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerStatus {
+    pub state: ServerState,
+    // Address the server listens on, while running.
+    pub addr: Option<String>,
+    pub config: Option<ServerConfig>,
+    pub players: Option<u32>,
+    pub uptime_secs: u64,
+    pub error: Option<String>,
+}
+
+impl ServerStatus {
+    fn stopped(state: ServerState, error: Option<String>) -> Self {
+        Self {
+            state,
+            addr: None,
+            config: None,
+            players: None,
+            uptime_secs: 0,
+            error,
+        }
+    }
+}
+
+pub type Notify = Arc<dyn Fn(&ServerStatus) + Send + Sync>;
+pub type Publish = Arc<dyn Fn(&ServerEvent) + Send + Sync>;
+
+struct Running {
+    config: ServerConfig,
+    addr: String,
+    sim: SimHandle,
+    shutdown: watch::Sender<bool>,
+    server: JoinHandle<Result<(), String>>,
+    discovery: JoinHandle<()>,
+    // Forwards sim events to `publish`; ends once the sim thread does.
+    events: JoinHandle<()>,
+    started: Instant,
+}
+
+pub struct Host {
+    // Used when the frontend hosts without a config of its own. Holds the
+    // reason instead if bf42lite.toml or the command line was invalid.
+    defaults: Result<ServerConfig, String>,
+    notify: Notify,
+    publish: Publish,
+    running: Mutex<Option<Running>>,
+}
+
+impl Host {
+    pub fn new(defaults: Result<ServerConfig, String>, notify: Notify, publish: Publish) -> Self {
+        Self {
+            defaults,
+            notify,
+            publish,
+            running: Mutex::new(None),
+        }
+    }
+
+    /// Start a server with `config` (or the launch config). Fails if one is
+    /// already running, the launch config was invalid or the port can't be
+    /// bound.
+    pub async fn start(&self, config: Option<ServerConfig>) -> Result<ServerStatus, String> {
+        let config = match config {
+            Some(config) => config,
+            None => self.defaults.clone()?,
+        };
+        config.validate()?;
+
+        let mut running = self.running.lock().await;
+        // A server that died on its own already reported it.
+        reap(&mut running).await;
+        if let Some(r) = running.as_ref() {
+            return Err(format!("a server is already running on {}", r.addr));
+        }
+
+        let listener = network::bind(&config.addr()).await?;
+        let addr = listener.local_addr().map_err(|e| e.to_string())?.to_string();
+
+        let sim = sim_loop::spawn(SimState::with_config(&config), config.tick());
+        let events = tokio::spawn({
+            let mut events = sim.events();
+            let publish = Arc::clone(&self.publish);
+            async move {
+                loop {
+                    match events.recv().await {
+                        Ok(event) => publish(&event),
+                        // A slow dashboard misses a few; the next stats catch it up.
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            }
+        });
+
+        let (shutdown, signal) = watch::channel(false);
+        let server = tokio::spawn({
+            let sim = sim.clone();
+            let notify = Arc::clone(&self.notify);
+            let net = config.net_config();
+            let requested = signal.clone();
+            async move {
+                let result = network::start_server(listener, sim, net, signal).await;
+                if !*requested.borrow() {
+                    let error = result.as_ref().err().cloned();
+                    notify(&ServerStatus::stopped(
+                        ServerState::Failed,
+                        Some(error.unwrap_or_else(|| "server stopped unexpectedly".to_string())),
+                    ));
+                }
+                result
+            }
+        });
+        let discovery = tokio::spawn({
+            let sim = sim.clone();
+            let config = config.clone();
+            async move {
+                if let Err(e) = discovery::serve(DISCOVERY_PORT, &config, sim).await {
+                    warn!(target: "net", error = %e, "LAN discovery disabled");
+                }
+            }
+        });
+
+        info!(
+            target: "net",
+            %addr,
+            tick_rate = config.server.tick_rate,
+            max_players = config.server.max_players,
+            map = %config.game.map,
+            mode = %config.game.mode,
+            movement = ?config.net.movement,
+            udp = config.net.udp,
+            relevance = config.net.relevance,
+            idle_kick_secs = config.net.idle_kick_secs,
+            "hosting"
+        );
+        *running = Some(Running {
+            config,
+            addr,
+            sim,
+            shutdown,
+            server,
+            discovery,
+            events,
+            started: Instant::now(),
+        });
+        let status = status_of(running.as_ref()).await;
+        (self.notify)(&status);
+        Ok(status)
+    }
+
+    /// Disconnect everyone and stop the sim.
+    pub async fn stop(&self) -> Result<(), String> {
+        let Some(r) = self.running.lock().await.take() else {
+            return Err("no server is running".to_string());
+        };
+        let _ = r.shutdown.send(true);
+        let result = r.server.await;
+        r.discovery.abort();
+        // Dropping the last handle ends the sim thread, after it has
+        // handled the disconnects.
+        drop(r.sim);
+        let mut events = r.events;
+        if tokio::time::timeout(EVENT_FLUSH_TIMEOUT, &mut events).await.is_err() {
+            events.abort();
+        }
+        info!(target: "net", addr = %r.addr, "stopped hosting");
+
+        (self.notify)(&ServerStatus::stopped(ServerState::Stopped, None));
+        match result {
+            Ok(result) => result,
+            Err(e) => Err(format!("server task failed: {}", e)),
+        }
+    }
+
+    pub async fn status(&self) -> ServerStatus {
+        let mut running = self.running.lock().await;
+        if let Some(failed) = reap(&mut running).await {
+            return failed;
+        }
+        status_of(running.as_ref()).await
+    }
+}
+
+async fn status_of(running: Option<&Running>) -> ServerStatus {
+    let Some(r) = running else {
+        return ServerStatus::stopped(ServerState::Stopped, None);
+    };
+    ServerStatus {
+        state: ServerState::Running,
+        addr: Some(r.addr.clone()),
+        config: Some(r.config.clone()),
+        players: r.sim.player_count().await.map(|n| n as u32),
+        uptime_secs: r.started.elapsed().as_secs(),
+        error: None,
+    }
+}
+
+/// Clear out a server that stopped without being asked to.
+async fn reap(running: &mut Option<Running>) -> Option<ServerStatus> {
+    if !running.as_ref().is_some_and(|r| r.server.is_finished()) {
+        return None;
+    }
+    let r = running.take()?;
+    r.discovery.abort();
+    r.events.abort();
+    let error = match r.server.await {
+        Ok(Ok(())) => "server stopped unexpectedly".to_string(),
+        Ok(Err(e)) => e,
+        Err(e) => format!("server task failed: {}", e),
+    };
+    Some(ServerStatus::stopped(ServerState::Failed, Some(error)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex as StdMutex;
+
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+    use tokio_tungstenite::tungstenite::Message;
+
+    use crate::protocol::{ClientMessage, PROTOCOL_VERSION};
+
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+    }
+
+    #[tokio::test]
+    async fn host_start_stop_round_trip() {
+        let events: Arc<StdMutex<Vec<ServerState>>> = Arc::default();
+        let notify: Notify = {
+            let events = Arc::clone(&events);
+            Arc::new(move |status: &ServerStatus| events.lock().unwrap().push(status.state))
+        };
+        let published: Arc<StdMutex<Vec<&'static str>>> = Arc::default();
+        let publish: Publish = {
+            let published = Arc::clone(&published);
+            Arc::new(move |event: &ServerEvent| published.lock().unwrap().push(event.name()))
+        };
+        let host = Host::new(Ok(ServerConfig::default()), notify, publish);
+        assert_eq!(host.status().await.state, ServerState::Stopped);
+        assert!(host.stop().await.is_err());
+
+        let mut config = ServerConfig::default();
+        config.server.bind = "127.0.0.1".to_string();
+        config.server.port = free_port();
+        let status = host.start(Some(config.clone())).await.unwrap();
+        assert_eq!(status.state, ServerState::Running);
+        assert_eq!(status.addr, Some(config.addr()));
+        assert_eq!(status.players, Some(0));
+
+        // One server per app, and invalid configs never get that far.
+        assert!(host.start(Some(config.clone())).await.unwrap_err().contains("already running"));
+        let mut bad = config.clone();
+        bad.server.tick_rate = 0;
+        assert!(host.start(Some(bad)).await.is_err());
+
+        let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/", config.addr())).await.unwrap();
+        let hello = ClientMessage::Hello {
+            protocol_version: PROTOCOL_VERSION,
+            name: "alice".to_string(),
+            requested_team: None,
+            compact: false,
+        };
+        ws.send(Message::Binary(rmp_serde::to_vec_named(&hello).unwrap())).await.unwrap();
+        assert!(matches!(ws.next().await, Some(Ok(Message::Binary(_)))));
+        assert_eq!(host.status().await.players, Some(1));
+
+        // Connected clients are told why they are being dropped.
+        host.stop().await.unwrap();
+        assert_eq!(host.status().await.state, ServerState::Stopped);
+        let close = tokio::time::timeout(Duration::from_secs(2), async {
+            while let Some(Ok(msg)) = ws.next().await {
+                if let Message::Close(frame) = msg {
+                    return frame;
+                }
+            }
+            None
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(close.code, CloseCode::Away);
+        // The dashboard saw the player come and go.
+        assert_eq!(
+            published.lock().unwrap().iter().filter(|e| e.starts_with("player-")).collect::<Vec<_>>(),
+            vec![&"player-joined", &"player-left"]
+        );
+        assert!(published.lock().unwrap().contains(&"match-phase"));
+        // The port is free again.
+        host.start(Some(config)).await.unwrap();
+        host.stop().await.unwrap();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![ServerState::Running, ServerState::Stopped, ServerState::Running, ServerState::Stopped]
+        );
+    }
+
+    #[tokio::test]
+    async fn bad_launch_config_is_reported_when_hosting() {
+        let invalid = Err("invalid config: server.port must not be 0".to_string());
+        let host = Host::new(invalid, Arc::new(|_| {}), Arc::new(|_| {}));
+        assert!(host.start(None).await.unwrap_err().contains("server.port"));
+        assert_eq!(host.status().await.state, ServerState::Stopped);
+
+        // A config from the frontend still works.
+        let mut config = ServerConfig::default();
+        config.server.bind = "127.0.0.1".to_string();
+        config.server.port = free_port();
+        host.start(Some(config)).await.unwrap();
+        host.stop().await.unwrap();
+    }
+}
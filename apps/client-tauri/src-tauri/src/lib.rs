@@ -1,65 +1,122 @@
 // apps/client-tauri/src-tauri/src/lib.rs
 
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
-
 mod sim;
+mod compact;
+mod config;
+mod delta;
+mod discovery;
+mod entity;
+mod events;
+mod host;
+mod logging;
 mod network;
+mod outbox;
 mod player;
+mod projectile;
+mod vehicle;
 mod protocol;
+mod relevance;
+mod session;
+mod sim_loop;
+mod stream;
 mod systems;
+mod udp;
+mod validate;
 pub mod maps;
 
-use crate::sim::SimState;
-use crate::protocol::ClientMessage;
+use std::sync::Arc;
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    // Shared state between the sim loop and the websocket server
-    let sim = Arc::new(Mutex::new(SimState::new()));
-    let inputs = Arc::new(Mutex::new(HashMap::<u32, ClientMessage>::new()));
+use tauri::{Emitter, Manager};
 
-    tauri::Builder::default()
-        .setup(move |_app| {
-            // --- SIMULATION TICK LOOP (authoritative conquest logic) ---
-            let sim_for_loop = sim.clone();
-            let inputs_for_loop = inputs.clone();
+use crate::config::ServerConfig;
+use crate::events::ServerEvent;
+use crate::host::{Host, ServerStatus};
+use tracing::error;
 
-            tauri::async_runtime::spawn(async move {
-                use tokio::time::sleep;
+/// Host a match. Without a config, uses bf42lite.toml and the command line.
+#[tauri::command]
+async fn host_server(host: tauri::State<'_, Host>, config: Option<ServerConfig>) -> Result<ServerStatus, String> {
+    host.start(config).await
+}
 
-                let mut last = Instant::now();
+/// Disconnect everyone and stop hosting.
+#[tauri::command]
+async fn stop_server(host: tauri::State<'_, Host>) -> Result<(), String> {
+    host.stop().await
+}
 
-                // Simple fixed tick ~20 Hz
-                loop {
-                    let now = Instant::now();
-                    let dt = now.duration_since(last).as_secs_f32();
-                    last = now;
+#[tauri::command]
+async fn server_status(host: tauri::State<'_, Host>) -> Result<ServerStatus, String> {
+    Ok(host.status().await)
+}
 
-                    {
-                        let mut sim_guard = sim_for_loop.lock().unwrap();
-                        let inputs_guard = inputs_for_loop.lock().unwrap();
-                        // This runs movement, combat and conquest, and builds a TickSnapshot.
-                        // Network code will call update() again when needed, that is fine for now.
-                        sim_guard.update(dt, &*inputs_guard);
-                    }
+/// Servers answering LAN discovery, fastest first.
+#[tauri::command]
+async fn list_lan_servers() -> Result<Vec<discovery::LanServer>, String> {
+    discovery::query(discovery::DEFAULT_QUERY_TIMEOUT).await
+}
 
-                    sleep(Duration::from_millis(50)).await;
-                }
-            });
+/// Change log verbosity at runtime, e.g. "info,net=debug".
+#[tauri::command]
+fn set_log_filter(filter: String) -> Result<(), String> {
+    logging::set_filter(&filter)
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    // BF42LITE_LOG=<filter> sets verbosity per target (falls back to RUST_LOG);
+    // BF42LITE_LOG_JSON=1 writes JSON lines instead of text.
+    let filter = std::env::var("BF42LITE_LOG")
+        .or_else(|_| std::env::var("RUST_LOG"))
+        .unwrap_or_else(|_| logging::DEFAULT_FILTER.to_string());
+    let json = std::env::var("BF42LITE_LOG_JSON").is_ok_and(|v| v == "1");
+    if let Err(e) = logging::init(&filter, json).or_else(|e| {
+        eprintln!("{}, using {:?}", e, logging::DEFAULT_FILTER);
+        logging::init(logging::DEFAULT_FILTER, json)
+    }) {
+        eprintln!("Logging disabled: {}", e);
+    }
 
-            // --- WEBSOCKET SERVER (clients connect here) ---
-            let sim_for_net = sim.clone();
-            let inputs_for_net = inputs.clone();
+    // bf42lite.toml (or --config <file>) plus command line overrides. A bad
+    // config doesn't stop the app; hosting with it reports the problem.
+    let config = ServerConfig::from_args(std::env::args().skip(1));
+    if let Err(e) = &config {
+        error!("{}", e);
+    }
+    // The menu hosts or joins; --host starts hosting right away, e.g. for a
+    // dedicated machine.
+    let host_on_launch = std::env::args().any(|a| a == "--host");
 
-            tauri::async_runtime::spawn(async move {
-                if let Err(e) =
-                    network::start_server("127.0.0.1:8080", sim_for_net, inputs_for_net).await
-                {
-                    eprintln!("[NET] WebSocket server error: {e}");
-                }
+    tauri::Builder::default()
+        .invoke_handler(tauri::generate_handler![
+            host_server,
+            stop_server,
+            server_status,
+            list_lan_servers,
+            set_log_filter
+        ])
+        .setup(move |app| {
+            // --- EMBEDDED SERVER (hosted from the menu, or at launch with --host) ---
+            let handle = app.handle().clone();
+            let notify = Arc::new(move |status: &ServerStatus| {
+                let _ = handle.emit(host::STATE_EVENT, status);
+            });
+            // Host dashboard: player-joined, player-left, match-phase,
+            // flag-captured and server-stats (once a second).
+            let handle = app.handle().clone();
+            let publish = Arc::new(move |event: &ServerEvent| {
+                let _ = handle.emit(event.name(), event);
             });
+            app.manage(Host::new(config, notify, publish));
+
+            if host_on_launch {
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = handle.state::<Host>().start(None).await {
+                        error!(target: "net", error = %e, "couldn't host");
+                    }
+                });
+            }
 
             Ok(())
         })
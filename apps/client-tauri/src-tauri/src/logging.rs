@@ -0,0 +1,49 @@
+// apps/client-tauri/src-tauri/src/logging.rs
+use std::sync::OnceLock;
+
+use tracing_subscriber::{fmt, prelude::*, reload, EnvFilter, Registry};
+
+// Log targets, one per subsystem: net, sim, movement, combat, conquest,
+// vehicles. Filters use `RUST_LOG` syntax, e.g. "info,net=debug,conquest=trace".
+pub const DEFAULT_FILTER: &str = "info";
+
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Install the global logger. With `json`, every event is written as one
+/// JSON object per line, fields included.
+pub fn init(filter: &str, json: bool) -> Result<(), String> {
+    let (filter, handle) = reload::Layer::new(parse(filter)?);
+    let output = if json {
+        fmt::layer().json().boxed()
+    } else {
+        fmt::layer().boxed()
+    };
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(output)
+        .try_init()
+        .map_err(|e| e.to_string())?;
+    let _ = FILTER.set(handle);
+    Ok(())
+}
+
+/// Change verbosity while the server runs.
+pub fn set_filter(filter: &str) -> Result<(), String> {
+    let handle = FILTER.get().ok_or("logging is not initialised")?;
+    handle.reload(parse(filter)?).map_err(|e| e.to_string())
+}
+
+fn parse(filter: &str) -> Result<EnvFilter, String> {
+    EnvFilter::try_new(filter).map_err(|e| format!("bad log filter {:?}: {}", filter, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_filters_are_rejected() {
+        assert!(parse("info,net=debug,conquest=trace").is_ok());
+        assert!(parse("net=loud").is_err());
+    }
+}
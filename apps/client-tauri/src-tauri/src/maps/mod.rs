@@ -1,3 +1,6 @@
 // apps/client-tauri/src-tauri/src/maps/mod.rs
 
 pub mod warehouse;
+
+// Maps a server can host, by the name used in the config file.
+pub const AVAILABLE: &[&str] = &["warehouse"];
@@ -1,7 +1,8 @@
 // apps/client-tauri/src-tauri/src/maps/warehouse.rs
 
-use crate::protocol::TeamId;
-use crate::sim::FlagZone;
+use crate::protocol::{TeamId, VehicleKind};
+use crate::sim::{Collider, FlagZone, KillVolume, WorldBounds};
+use crate::vehicle::VehicleSpawn;
 
 /// Create the Conquest flags for the MVP Warehouse map.
 ///
@@ -40,3 +41,52 @@ pub fn create_flags() -> Vec<FlagZone> {
         },
     ]
 }
+
+/// Playable area for Warehouse. The floor sits at y = 0, so anything far
+/// below it has fallen through the world.
+pub fn world_bounds() -> WorldBounds {
+    WorldBounds {
+        min_x: -100.0,
+        min_y: -20.0,
+        min_z: -100.0,
+        max_x: 100.0,
+        max_y: 100.0,
+        max_z: 100.0,
+    }
+}
+
+/// Warehouse is flat and enclosed, so it has no kill volumes of its own yet.
+pub fn create_kill_volumes() -> Vec<KillVolume> {
+    Vec::new()
+}
+
+/// Solid geometry for projectile bounces and explosion line of sight.
+/// The client only renders the ground plane so far, so there is nothing
+/// to collide with apart from the floor and world bounds.
+pub fn create_colliders() -> Vec<Collider> {
+    Vec::new()
+}
+
+/// Vehicle spawns: a jeep near flag 2 and a tank near flag 3.
+pub fn create_vehicle_spawns() -> Vec<VehicleSpawn> {
+    vec![
+        VehicleSpawn {
+            id: 1,
+            kind: VehicleKind::Jeep,
+            x: 25.0,
+            y: 0.0,
+            z: 20.0,
+            yaw: 0.0,
+            respawn_time: 20.0,
+        },
+        VehicleSpawn {
+            id: 2,
+            kind: VehicleKind::Tank,
+            x: -25.0,
+            y: 0.0,
+            z: -20.0,
+            yaw: std::f32::consts::PI,
+            respawn_time: 45.0,
+        },
+    ]
+}
@@ -1,232 +1,647 @@
 // apps/client-tauri/src-tauri/src/network.rs
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::Mutex as AsyncMutex;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use futures_util::stream::SplitStream;
 use futures_util::{SinkExt, StreamExt};
-use tokio_tungstenite::{accept_async, tungstenite::protocol::Message};
+use tokio_tungstenite::{accept_hdr_async, WebSocketStream};
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::{CloseFrame, Message};
 use rmp_serde::to_vec as rmp_to_vec;
+use tracing::{debug, info, trace, warn};
 
-use crate::sim::SimState;
-use crate::protocol::{ClientMessage, TickSnapshot, ServerEnvelope};
+use crate::outbox::{self, SNAPSHOT_CAPACITY};
+use crate::relevance::RelevanceConfig;
+use crate::sim_loop::{JoinRequest, SimCommand, SimHandle};
+use crate::protocol::{ClientMessage, TeamId, ServerEnvelope, PROTOCOL_VERSION};
+use crate::session::DisconnectReason;
+use crate::stream::SnapshotStream;
+use crate::udp::UdpHub;
+use crate::validate::{GuardConfig, InputGuard, Verdict};
+use crate::vehicle::VehicleRequest;
 
+// How long a new connection has to send `ClientMessage::Hello`.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(5);
+// Server pings every client this often. Pongs double as RTT samples.
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+// Nothing at all (not even a pong) for this long: the connection is dead.
+const KEEPALIVE_TIMEOUT: Duration = Duration::from_secs(15);
+// Pongs but no client messages for this long: the game itself has hung.
+const INPUT_TIMEOUT: Duration = Duration::from_secs(30);
+// Time the writer gets to flush the close frame before it is dropped.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+// Time connections get to close after a shutdown before they are cut off.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Connection policy.
+#[derive(Debug, Clone, Copy)]
+pub struct NetConfig {
+    // Kick players who show no activity (movement, looking, shooting) for
+    // this long. None disables idle kicks.
+    pub idle_kick: Option<Duration>,
+    // Also listen for UDP on the same port. When false (or the bind fails)
+    // everything goes over the WebSocket.
+    pub udp: bool,
+    // Accept clients asking for the compact snapshot encoding.
+    pub compact: bool,
+    // Per-client interest management. None sends every entity to everyone.
+    pub relevance: Option<RelevanceConfig>,
+    // Disconnect clients whose outbound queue has been overflowing this long.
+    pub max_behind: Duration,
+    // Client message rate limit and violation kicks.
+    pub guard: GuardConfig,
+}
+
+impl Default for NetConfig {
+    fn default() -> Self {
+        Self {
+            idle_kick: Some(Duration::from_secs(300)),
+            udp: true,
+            compact: true,
+            relevance: Some(RelevanceConfig::default()),
+            max_behind: Duration::from_secs(5),
+            guard: GuardConfig::default(),
+        }
+    }
+}
+
+/// Listen for WebSocket clients. Bound separately from `start_server` so
+/// the caller hears about a taken port right away.
+pub async fn bind(addr: &str) -> Result<TcpListener, String> {
+    TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("can't listen on {}: {}", addr, e))
+}
+
+/// Serve clients until `shutdown` flips (or its sender is dropped), then
+/// close every connection and return.
 pub async fn start_server(
-    addr: &str,
-    sim: Arc<Mutex<SimState>>,
-    inputs: Arc<Mutex<HashMap<u32, ClientMessage>>>,
+    listener: TcpListener,
+    sim: SimHandle,
+    config: NetConfig,
+    mut shutdown: watch::Receiver<bool>,
 ) -> Result<(), String> {
-    let listener = TcpListener::bind(addr)
-        .await
-        .map_err(|e| e.to_string())?;
+    let addr = listener.local_addr().map_err(|e| e.to_string())?.to_string();
+    let mut udp_task = None;
+    let udp = if config.udp {
+        match UdpHub::bind(&addr, Arc::clone(sim.traffic())).await {
+            Ok((hub, task)) => {
+                info!(target: "net", %addr, "UDP listening");
+                udp_task = Some(tokio::spawn(task.run()));
+                Some(hub)
+            }
+            Err(e) => {
+                warn!(target: "net", error = %e, "UDP unavailable, using WebSocket only");
+                None
+            }
+        }
+    } else {
+        info!(target: "net", "UDP disabled, using WebSocket only");
+        None
+    };
 
     // Accept loop
-    while let Ok((stream, _)) = listener.accept().await {
-        tokio::spawn(accept_connection(
-            stream,
-            sim.clone(),
-            inputs.clone(),
-        ));
+    let mut connections = JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let Ok((stream, _)) = accepted else { break };
+                connections.spawn(accept_connection(
+                    stream,
+                    sim.clone(),
+                    udp.clone(),
+                    config,
+                    shutdown.clone(),
+                ));
+            }
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            _ = shutdown.changed() => break,
+        }
     }
+    drop(listener);
 
+    // Connections watch the same signal and close with ServerShutdown.
+    let drained = tokio::time::timeout(SHUTDOWN_TIMEOUT, async {
+        while connections.join_next().await.is_some() {}
+    })
+    .await;
+    if drained.is_err() {
+        connections.abort_all();
+    }
+    if let Some(task) = udp_task {
+        task.abort();
+    }
+    info!(target: "net", %addr, "server stopped");
     Ok(())
 }
 
+// The handshake callback's error type is fixed by tungstenite.
+#[allow(clippy::result_large_err)]
 async fn accept_connection(
     stream: TcpStream,
-    sim: Arc<Mutex<SimState>>,
-    inputs: Arc<Mutex<HashMap<u32, ClientMessage>>>,
+    sim: SimHandle,
+    udp: Option<UdpHub>,
+    config: NetConfig,
+    mut shutdown: watch::Receiver<bool>,
 ) {
     let addr = stream
         .peer_addr()
         .expect("Failed to get peer address");
-    println!("Client connected: {}", addr);
+    info!(target: "net", %addr, "client connected");
 
-    let ws_stream = match accept_async(stream).await {
+    // A reconnecting client passes its session token: ws://host:port/?session=<token>
+    let mut resume_token: Option<String> = None;
+    let ws_stream = match accept_hdr_async(stream, |req: &Request, resp: Response| {
+        resume_token = session_from_query(req.uri().query());
+        Ok(resp)
+    })
+    .await
+    {
         Ok(ws) => ws,
         Err(e) => {
-            println!("Handshake error: {}", e);
+            warn!(target: "net", %addr, error = %e, "handshake failed");
             return;
         }
     };
 
-    // Split stream and wrap writer
-    let (ws_write_raw, mut ws_read) = ws_stream.split();
-    let ws_write = Arc::new(AsyncMutex::new(ws_write_raw));
+    let (mut ws_write, mut ws_read) = ws_stream.split();
 
-    // 1) Assign Player ID
-    let my_id = {
-        let mut s = sim.lock().unwrap();
-        let id = s.players.len() as u32 + 1;
-        s.handle_join(id);
-        id
+    // 1) Handshake: the first message must be a Hello with our protocol version
+    let (name, requested_team, wants_compact) = match read_hello(&mut ws_read).await {
+        Ok(hello) => hello,
+        Err(reason) => {
+            warn!(target: "net", %addr, %reason, "rejected");
+            let _ = ws_write
+                .send(Message::Close(Some(CloseFrame {
+                    code: CloseCode::Policy,
+                    reason: reason.into(),
+                })))
+                .await;
+            return;
+        }
     };
 
-    // 2) Send Initial Snapshot
-    let initial_bin = {
-        let mut s = sim.lock().unwrap();
-        let snapshot: TickSnapshot = s.update(0.0, &HashMap::new());
-        rmp_to_vec(&ServerEnvelope {
-            your_id: my_id,
-            snapshot,
+    // 2) Join (or resume) through the sim task
+    let joined = sim
+        .join(JoinRequest {
+            name,
+            requested_team,
+            resume_token,
+            compact: wants_compact && config.compact,
         })
-        .unwrap()
+        .await;
+    let joined = match joined {
+        Ok(joined) => joined,
+        Err(e) => {
+            warn!(target: "net", %addr, error = %e, "join failed");
+            return;
+        }
     };
+    let (my_id, my_eid) = (joined.conn_id, joined.eid);
 
-    if ws_write
-        .lock()
-        .await
-        .send(Message::Binary(initial_bin))
-        .await
-        .is_err()
-    {
-        println!("Failed to send initial snapshot to {}", addr);
+    // Everything after the handshake goes through the outbox; only the
+    // writer touches the socket once it starts.
+    let (outbox, writer, mut too_slow) = outbox::channel(SNAPSHOT_CAPACITY, config.max_behind);
+
+    // Inputs the client sends over UDP arrive here.
+    let (udp_token, mut udp_rx) = match &udp {
+        Some(hub) => {
+            let (token, rx) = hub.register(my_id, outbox.clone());
+            (Some(token), Some(rx))
+        }
+        None => (None, None),
+    };
+
+    // Newest snapshot tick the client acked; 0 = no baseline.
+    let acked = Arc::new(AtomicU64::new(0));
+    // Newest snapshot tick sent; acks beyond it are bogus.
+    let sent_tick = Arc::new(AtomicU64::new(0));
+
+    // 3) Send Initial Snapshot. `joined.frames` picks up at the next tick.
+    let mut frames_rx = joined.frames;
+    let compact_format = joined.compact;
+    let mut stream = SnapshotStream::new(my_eid, compact_format, config.relevance, joined.colliders);
+    let initial = stream.first(&joined.snapshot);
+    sent_tick.store(initial.tick, Ordering::Relaxed);
+    let initial_bin = rmp_to_vec(&ServerEnvelope {
+        your_id: my_eid,
+        snapshot: Some(initial),
+        session_token: joined.session_token,
+        udp_port: udp.as_ref().and_then(|hub| hub.port()),
+        udp_token,
+        delta: None,
+        compact: compact_format,
+    })
+    .unwrap();
+
+    sim.traffic().sent(initial_bin.len());
+    if ws_write.send(Message::Binary(initial_bin)).await.is_err() {
+        warn!(target: "net", %addr, "failed to send initial snapshot");
+        if let Some(hub) = &udp {
+            hub.unregister(my_id);
+        }
         return;
     }
 
-    // 3) Spawn Snapshot Sender (20 Hz Background Loop)
-    {
-        let sim_for_send = Arc::clone(&sim);
-        let inputs_for_send = Arc::clone(&inputs);
-        let ws_for_send = Arc::clone(&ws_write);
-        let my_id_send = my_id;
+    let mut writer = {
+        let traffic = Arc::clone(sim.traffic());
+        tokio::spawn(writer.run(ws_write, move |len| traffic.sent(len)))
+    };
 
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_millis(50));
-            let mut last_instant = Instant::now();
+    // 4) Spawn Snapshot Sender, fed by the tick loop
+    let sender = {
+        let outbox_for_send = outbox.clone();
+        let udp_for_send = udp.clone();
+        let acked_for_send = Arc::clone(&acked);
+        let sent_for_send = Arc::clone(&sent_tick);
 
+        tokio::spawn(async move {
             loop {
-                interval.tick().await;
-
-                // Compute delta time in seconds
-                let now = Instant::now();
-                let dt = (now - last_instant).as_secs_f32();
-                last_instant = now;
-
-                // Advance sim and get snapshot using current input map
-                let snapshot: TickSnapshot = {
-                    let mut s = sim_for_send.lock().unwrap();
-                    let input_map = inputs_for_send.lock().unwrap();
-                    s.update(dt, &*input_map)
+                let frame = match frames_rx.recv().await {
+                    Ok(frame) => frame,
+                    // Fell behind; newer frames supersede the skipped ones.
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
                 };
+                if frame.snapshot.tick <= stream.last_tick() {
+                    continue;
+                }
 
-                let envelope = ServerEnvelope {
-                    your_id: my_id_send,
-                    snapshot,
-                };
+                let bytes = stream.encode(&frame, acked_for_send.load(Ordering::Relaxed));
+                sent_for_send.store(frame.snapshot.tick, Ordering::Relaxed);
+                match &udp_for_send {
+                    Some(hub) => hub.send_snapshot(my_id, bytes),
+                    None => outbox_for_send.push_snapshot(bytes),
+                }
+            }
+        })
+    };
+
+    // 5) Read Client Inputs, with keepalive pings and idle checks
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    // Ping payloads carry the send time relative to this.
+    let epoch = Instant::now();
+    let mut last_heard = Instant::now();
+    let mut last_message = Instant::now();
+    let mut last_activity = Instant::now();
+    let mut guard = InputGuard::new(config.guard, Instant::now());
+    // Last input-type message, to tell real activity from idle repeats.
+    let mut last_input: Option<ClientMessage> = None;
 
-                let bytes = rmp_to_vec(&envelope).unwrap();
-                if ws_for_send
-                    .lock()
-                    .await
-                    .send(Message::Binary(bytes))
-                    .await
-                    .is_err()
-                {
-                    break; // client disconnected
+    let reason = loop {
+        let msg = tokio::select! {
+            msg = ws_read.next() => msg,
+            client_msg = recv_udp(&mut udp_rx) => {
+                last_heard = Instant::now();
+                last_message = Instant::now();
+                match guard.check(client_msg, sent_tick.load(Ordering::Relaxed), Instant::now()) {
+                    Verdict::Accept(client_msg) => {
+                        handle_message(client_msg, my_id, &sim, &acked, &mut last_input, &mut last_activity)
+                    }
+                    Verdict::Reject(why) => debug!(target: "net", player = my_id, %why, "dropped UDP message"),
+                    Verdict::Kick(why) => break DisconnectReason::Kicked(why),
                 }
+                continue;
             }
-        });
-    }
+            _ = shutdown.changed() => break DisconnectReason::ServerShutdown,
+            _ = ping.tick() => {
+                if last_heard.elapsed() >= KEEPALIVE_TIMEOUT {
+                    info!(target: "net", player = my_id, "stopped answering pings");
+                    break DisconnectReason::Timeout;
+                }
+                if last_message.elapsed() >= INPUT_TIMEOUT {
+                    info!(target: "net", player = my_id, secs = INPUT_TIMEOUT.as_secs(), "sent nothing");
+                    break DisconnectReason::Timeout;
+                }
+                if let Ok(stats) = too_slow.try_recv() {
+                    warn!(
+                        target: "net",
+                        player = my_id,
+                        queued = stats.depth,
+                        dropped = stats.dropped,
+                        "can't keep up"
+                    );
+                    break DisconnectReason::TooSlow;
+                }
+                if let Some(limit) = config.idle_kick {
+                    if last_activity.elapsed() >= limit {
+                        break DisconnectReason::Kicked(format!("idle for {}s", limit.as_secs()));
+                    }
+                }
+                let sent = (epoch.elapsed().as_nanos() as u64).to_be_bytes().to_vec();
+                outbox.push(Message::Ping(sent));
+                continue;
+            }
+        };
+
+        let msg = match msg {
+            Some(Ok(msg)) => {
+                sim.traffic().received(msg.len());
+                msg
+            }
+            Some(Err(e)) => break DisconnectReason::Error(e.to_string()),
+            None => break DisconnectReason::Error("connection dropped".to_string()),
+        };
+        last_heard = Instant::now();
 
-    // 4) Read Client Inputs
-    while let Some(msg) = ws_read.next().await {
         match msg {
-            Ok(Message::Binary(bin)) => {
-                // Debug: see that we got a binary message
-                println!(
-                    "[NET] Received Binary message of {} bytes from player {}",
-                    bin.len(),
-                    my_id
-                );
+            Message::Binary(bin) => {
+                trace!(target: "net", player = my_id, bytes = bin.len(), "received binary message");
 
                 match rmp_serde::from_slice::<ClientMessage>(&bin) {
                     Ok(client_msg) => {
-                        // Debug: confirm decode worked
-                        println!(
-                            "[NET] Decoded ClientMessage from player {}: {:?}",
-                            my_id, client_msg
-                        );
-
-                        // Store latest input for this player
-                        inputs.lock().unwrap().insert(my_id, client_msg);
+                        trace!(target: "net", player = my_id, message = ?client_msg, "decoded message");
+                        last_message = Instant::now();
+                        match guard.check(client_msg, sent_tick.load(Ordering::Relaxed), Instant::now()) {
+                            Verdict::Accept(client_msg) => {
+                                handle_message(client_msg, my_id, &sim, &acked, &mut last_input, &mut last_activity)
+                            }
+                            Verdict::Reject(why) => debug!(target: "net", player = my_id, %why, "dropped message"),
+                            Verdict::Kick(why) => break DisconnectReason::Kicked(why),
+                        }
                         // Note: we do NOT send a snapshot here anymore.
                         // The periodic GameLoop::start task handles snapshots.
                     }
                     Err(e) => {
-                        eprintln!(
-                            "[NET] Failed to decode ClientMessage from player {}: {:?} ({} bytes)",
-                            my_id,
-                            e,
-                            bin.len()
+                        warn!(
+                            target: "net",
+                            player = my_id,
+                            error = ?e,
+                            bytes = bin.len(),
+                            "failed to decode message"
                         );
+                        if let Verdict::Kick(why) = guard.strike(format!("undecodable message: {}", e), Instant::now()) {
+                            break DisconnectReason::Kicked(why);
+                        }
                     }
                 }
             }
-            Ok(Message::Close(_)) => break,
+            Message::Pong(payload) => {
+                if let Ok(sent) = <[u8; 8]>::try_from(payload.as_slice()) {
+                    let sent = Duration::from_nanos(u64::from_be_bytes(sent));
+                    let rtt = epoch.elapsed().saturating_sub(sent);
+                    sim.send(SimCommand::Rtt(my_id, rtt));
+                }
+            }
+            Message::Close(_) => break DisconnectReason::ClientClosed,
             _ => {}
         }
+    };
+
+    // 6) Disconnect Cleanup
+    sender.abort();
+    if let Some(hub) = &udp {
+        hub.unregister(my_id);
+    }
+    if let Some(code) = match reason {
+        DisconnectReason::Timeout | DisconnectReason::ServerShutdown => Some(CloseCode::Away),
+        DisconnectReason::Kicked(_) => Some(CloseCode::Policy),
+        DisconnectReason::TooSlow => Some(CloseCode::Again),
+        _ => None,
+    } {
+        let frame = CloseFrame {
+            code,
+            reason: reason.to_string().into(),
+        };
+        outbox.push(Message::Close(Some(frame)));
+    }
+    outbox.close();
+    match tokio::time::timeout(FLUSH_TIMEOUT, &mut writer).await {
+        Ok(Ok(stats)) => {
+            debug!(target: "net", player = my_id, peak = stats.peak, dropped = stats.dropped, "outbox stats")
+        }
+        Ok(Err(_)) => {}
+        Err(_) => writer.abort(),
     }
+    sim.send(SimCommand::Disconnect(my_id, reason));
+    info!(target: "net", %addr, "client disconnected");
+}
 
-    // 5) Disconnect Cleanup
-    {
-        let mut s = sim.lock().unwrap();
-        s.handle_disconnect(my_id);
+/// Apply one decoded message from either channel.
+fn handle_message(
+    client_msg: ClientMessage,
+    my_id: u32,
+    sim: &SimHandle,
+    acked: &AtomicU64,
+    last_input: &mut Option<ClientMessage>,
+    last_activity: &mut Instant,
+) {
+    match client_msg {
+        // Only valid as the first message.
+        ClientMessage::Hello { .. } => {}
+        // UDP acks can arrive out of order; only 0 (baseline lost) goes backwards.
+        ClientMessage::SnapshotAck { tick: 0 } => acked.store(0, Ordering::Relaxed),
+        ClientMessage::SnapshotAck { tick } => {
+            acked.fetch_max(tick, Ordering::Relaxed);
+        }
+        // One-shot requests are queued so the next Input can't overwrite them.
+        ClientMessage::EnterVehicle { vehicle_id, seat } => {
+            *last_activity = Instant::now();
+            sim.send(SimCommand::Vehicle(my_id, VehicleRequest::Enter { vehicle_id, seat }));
+        }
+        ClientMessage::ExitVehicle => {
+            *last_activity = Instant::now();
+            sim.send(SimCommand::Vehicle(my_id, VehicleRequest::Exit));
+        }
+        // Store latest input for this player
+        _ => {
+            if is_activity(&client_msg, last_input.as_ref()) {
+                *last_activity = Instant::now();
+            }
+            *last_input = Some(client_msg.clone());
+            sim.send(SimCommand::Input(my_id, client_msg));
+        }
     }
-    {
-        let mut inp = inputs.lock().unwrap();
-        inp.remove(&my_id);
+}
+
+/// Next input from the UDP channel; never resolves when UDP is off.
+async fn recv_udp(rx: &mut Option<UnboundedReceiver<ClientMessage>>) -> ClientMessage {
+    match rx {
+        Some(rx) => match rx.recv().await {
+            Some(msg) => msg,
+            None => std::future::pending().await,
+        },
+        None => std::future::pending().await,
     }
-    println!("Client {} disconnected", addr);
 }
-use serde::{Deserialize, Serialize};
-use crate::protocol::{Transform, TeamId};
-
-pub const MAX_HEALTH: f32 = 100.0;
-pub const RESPAWN_TIME: f32 = 5.0;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Player {
-    pub id: u32,
-    pub transform: Transform,
-    pub velocity: (f32, f32, f32),
-    pub team: TeamId,
-    pub health: f32,
-    pub max_health: f32,
-    pub is_dead: bool,
-    pub respawn_timer: f32,
-    pub fire_cooldown: f32, 
-    pub score_kills: u32,
-    pub score_deaths: u32,
-    pub class_id: u8,
+
+/// Whether a message shows a player at the keyboard, as opposed to a client
+/// streaming the same idle input. `prev` is the last message from that player.
+fn is_activity(msg: &ClientMessage, prev: Option<&ClientMessage>) -> bool {
+    match (msg, prev) {
+        (ClientMessage::Input { axes, .. }, prev) => {
+            let pressing = axes.forward != 0.0
+                || axes.right != 0.0
+                || axes.jump
+                || axes.shoot
+                || axes.reload
+                || axes.sprint;
+            let looked = match prev {
+                Some(ClientMessage::Input { axes: p, .. }) => p.yaw != axes.yaw || p.pitch != axes.pitch,
+                _ => true,
+            };
+            pressing || looked
+        }
+        (
+            ClientMessage::StateUpdate { x, y, z, yaw, pitch, .. },
+            Some(ClientMessage::StateUpdate { x: px, y: py, z: pz, yaw: pyaw, pitch: ppitch, .. }),
+        ) => (x, y, z, yaw, pitch) != (px, py, pz, pyaw, ppitch),
+        _ => true,
+    }
 }
 
-impl Player {
-    pub fn new(id: u32, team: TeamId) -> Self {
-        Self {
-            id,
-            transform: Transform { x: 0.0, y: 2.0, z: 0.0, yaw: 0.0, pitch: 0.0 },
-            velocity: (0.0, 0.0, 0.0),
-            team,
-            health: MAX_HEALTH,
-            max_health: MAX_HEALTH,
-            is_dead: false,
-            respawn_timer: 0.0,
-            fire_cooldown: 0.0,
-            score_kills: 0,
-            score_deaths: 0,
-            class_id: 0,
-        }
-    }
-
-    pub fn respawn(&mut self) {
-        self.health = self.max_health;
-        self.is_dead = false;
-        self.fire_cooldown = 0.0;
-        self.transform.x = 0.0;
-        self.transform.z = 0.0; 
-        self.transform.y = 2.0;
-        println!("[GAME] Player {} respawned", self.id);
+/// Wait for the client's `Hello` and check its protocol version.
+/// Returns the requested name, team and whether the client wants compact
+/// snapshots, or the reason to turn the client away.
+async fn read_hello(
+    ws_read: &mut SplitStream<WebSocketStream<TcpStream>>,
+) -> Result<(String, Option<TeamId>, bool), String> {
+    let first = tokio::time::timeout(HELLO_TIMEOUT, async {
+        loop {
+            match ws_read.next().await {
+                Some(Ok(Message::Binary(bin))) => return Ok(bin),
+                Some(Ok(Message::Close(_))) | None => {
+                    return Err("closed before hello".to_string())
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Err(e.to_string()),
+            }
+        }
+    })
+    .await
+    .map_err(|_| format!("no hello within {}s", HELLO_TIMEOUT.as_secs()))??;
+
+    match rmp_serde::from_slice::<ClientMessage>(&first) {
+        Ok(ClientMessage::Hello { protocol_version, name, requested_team, compact }) => {
+            if protocol_version != PROTOCOL_VERSION {
+                return Err(format!(
+                    "protocol version mismatch: server {}, client {}",
+                    PROTOCOL_VERSION, protocol_version
+                ));
+            }
+            Ok((name, requested_team, compact))
+        }
+        Ok(_) => Err("expected hello as first message".to_string()),
+        Err(e) => Err(format!("bad hello: {}", e)),
+    }
+}
+
+fn session_from_query(query: Option<&str>) -> Option<String> {
+    query?
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, value)| *key == "session" && !value.is_empty())
+        .map(|(_, value)| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::ClientAxes;
+    use crate::sim::SimState;
+    use crate::sim_loop;
+
+    fn input(tick: u32, forward: f32, yaw: f32) -> ClientMessage {
+        let axes = ClientAxes { forward, right: 0.0, jump: false, shoot: false, reload: false, sprint: false, yaw, pitch: 0.0 };
+        ClientMessage::Input { tick, axes }
+    }
+
+    fn state(tick: u32, x: f32) -> ClientMessage {
+        ClientMessage::StateUpdate { tick, x, y: 0.0, z: 0.0, yaw: 0.0, pitch: 0.0, sprint: false }
+    }
+
+    #[test]
+    fn idle_repeats_are_not_activity() {
+        // The same idle input over and over, only the tick moving on.
+        assert!(!is_activity(&input(2, 0.0, 0.5), Some(&input(1, 0.0, 0.5))));
+        assert!(!is_activity(&state(2, 1.0), Some(&state(1, 1.0))));
+
+        // Looking around, holding a key, or moving.
+        assert!(is_activity(&input(2, 0.0, 0.6), Some(&input(1, 0.0, 0.5))));
+        assert!(is_activity(&input(2, -1.0, 0.5), Some(&input(1, -1.0, 0.5))));
+        assert!(is_activity(&state(2, 1.5), Some(&state(1, 1.0))));
+
+        // Nothing to compare with yet, or a different kind of message.
+        assert!(is_activity(&input(1, 0.0, 0.5), None));
+        assert!(is_activity(&state(2, 1.0), Some(&input(1, 0.0, 0.0))));
+    }
+
+    #[tokio::test]
+    async fn idle_players_are_kicked() {
+        let listener = bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let sim = sim_loop::spawn(SimState::new(), Duration::from_millis(50));
+        let config = NetConfig { idle_kick: Some(Duration::from_secs(1)), udp: false, ..Default::default() };
+        let (_stop, shutdown) = watch::channel(false);
+        tokio::spawn(start_server(listener, sim, config, shutdown));
+
+        let mut clients = Vec::new();
+        for _ in 0..2 {
+            let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}/", addr)).await.unwrap();
+            let hello = ClientMessage::Hello {
+                protocol_version: PROTOCOL_VERSION,
+                name: "tester".to_string(),
+                requested_team: None,
+                compact: false,
+            };
+            ws.send(Message::Binary(rmp_to_vec(&hello).unwrap())).await.unwrap();
+            clients.push(ws);
+        }
+
+        // One client streams the same idle input, the other keeps looking around.
+        let started = Instant::now();
+        let mut tick = 0;
+        let reason = loop {
+            assert!(started.elapsed() < Duration::from_secs(5), "nobody was kicked");
+            tick += 1;
+            clients[0].send(Message::Binary(rmp_to_vec(&input(tick, 0.0, 0.0)).unwrap())).await.unwrap();
+            let look = input(tick, 0.0, tick as f32 * 0.01);
+            clients[1].send(Message::Binary(rmp_to_vec(&look).unwrap())).await.unwrap();
+
+            let closed = tokio::time::timeout(Duration::from_millis(100), async {
+                while let Some(Ok(msg)) = clients[0].next().await {
+                    if let Message::Close(frame) = msg {
+                        return frame;
+                    }
+                }
+                None
+            })
+            .await;
+            if let Ok(frame) = closed {
+                break frame.unwrap();
+            }
+        };
+        assert_eq!(reason.code, CloseCode::Policy);
+        assert!(reason.reason.contains("idle"));
+
+        // The active one keeps looking around and outlasts another idle
+        // period. Snapshots buffered above don't count; only a Close does.
+        let until = Instant::now() + Duration::from_secs(2);
+        while Instant::now() < until {
+            tick += 1;
+            let look = input(tick, 0.0, tick as f32 * 0.01);
+            clients[1].send(Message::Binary(rmp_to_vec(&look).unwrap())).await.unwrap();
+            let dropped = tokio::time::timeout(Duration::from_millis(100), async {
+                loop {
+                    match clients[1].next().await {
+                        Some(Ok(Message::Close(frame))) => return format!("closed: {:?}", frame),
+                        Some(Err(e)) => return e.to_string(),
+                        None => return "disconnected".to_string(),
+                        Some(Ok(_)) => continue,
+                    }
+                }
+            })
+            .await;
+            if let Ok(why) = dropped {
+                panic!("active client was dropped: {}", why);
+            }
+        }
     }
 }
@@ -0,0 +1,272 @@
+// apps/client-tauri/src-tauri/src/outbox.rs
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use futures_util::{Sink, SinkExt};
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::sync::oneshot;
+use tokio_tungstenite::tungstenite::protocol::Message;
+
+// Snapshots queued for one client before the oldest start being dropped.
+pub const SNAPSHOT_CAPACITY: usize = 4;
+
+enum Outgoing {
+    // Superseded by the next one; may be dropped.
+    Snapshot(Vec<u8>),
+    // Pings, close frames and anything else that must arrive.
+    Reliable(Message),
+    // Nothing after this is sent.
+    Close,
+}
+
+/// Depth counters for one client's outbound queue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutboxStats {
+    pub depth: usize,
+    pub peak: usize,
+    pub dropped: u64,
+}
+
+/// Sending side of one client's outbound WebSocket queue. Cheap to clone;
+/// messages go over a channel to the `Writer`, which owns the queue, so a
+/// stalled socket never blocks whoever is sending.
+#[derive(Clone)]
+pub struct Outbox {
+    tx: UnboundedSender<Outgoing>,
+}
+
+impl Outbox {
+    /// Queue a snapshot. The writer drops the oldest queued one if the client
+    /// is `capacity` snapshots behind. Each snapshot is either full or a delta
+    /// against one the client acked, so any of them can be skipped.
+    pub fn push_snapshot(&self, bytes: Vec<u8>) {
+        let _ = self.tx.send(Outgoing::Snapshot(bytes));
+    }
+
+    /// Queue a message that is never dropped.
+    pub fn push(&self, msg: Message) {
+        let _ = self.tx.send(Outgoing::Reliable(msg));
+    }
+
+    /// Stop sending. The writer flushes what is already queued, then ends.
+    pub fn close(&self) {
+        let _ = self.tx.send(Outgoing::Close);
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    items: VecDeque<Outgoing>,
+    snapshots: usize,
+    dropped: u64,
+    peak: usize,
+    // Set when a snapshot is dropped, cleared when the queue drains.
+    behind_since: Option<Instant>,
+}
+
+impl Queue {
+    fn push(&mut self, item: Outgoing, capacity: usize) {
+        if matches!(item, Outgoing::Snapshot(_)) {
+            if self.snapshots >= capacity {
+                if let Some(i) = self.items.iter().position(|m| matches!(m, Outgoing::Snapshot(_))) {
+                    self.items.remove(i);
+                    self.snapshots -= 1;
+                    self.dropped += 1;
+                }
+                self.behind_since.get_or_insert_with(Instant::now);
+            }
+            self.snapshots += 1;
+        }
+        self.items.push_back(item);
+        self.peak = self.peak.max(self.items.len());
+    }
+
+    fn pop(&mut self) -> Option<Message> {
+        let item = self.items.pop_front()?;
+        if self.items.is_empty() {
+            self.behind_since = None;
+        }
+        match item {
+            Outgoing::Snapshot(bytes) => {
+                self.snapshots -= 1;
+                Some(Message::Binary(bytes))
+            }
+            Outgoing::Reliable(msg) => Some(msg),
+            // Never queued; `Writer::accept` stops at it.
+            Outgoing::Close => None,
+        }
+    }
+
+    /// How long the client has been dropping snapshots without catching up.
+    fn behind_for(&self) -> Duration {
+        self.behind_since.map_or(Duration::ZERO, |since| since.elapsed())
+    }
+
+    fn stats(&self) -> OutboxStats {
+        OutboxStats {
+            depth: self.items.len(),
+            peak: self.peak,
+            dropped: self.dropped,
+        }
+    }
+}
+
+/// Receiving side of an `Outbox`: the only owner of the queue and the only
+/// thing that touches the socket.
+pub struct Writer {
+    rx: UnboundedReceiver<Outgoing>,
+    queue: Queue,
+    capacity: usize,
+    max_behind: Duration,
+    // Fired once if the client stays behind for `max_behind`.
+    too_slow: Option<oneshot::Sender<OutboxStats>>,
+}
+
+/// A new outbox and its writer. The receiver hears once if the client drops
+/// snapshots for `max_behind` without catching up.
+pub fn channel(capacity: usize, max_behind: Duration) -> (Outbox, Writer, oneshot::Receiver<OutboxStats>) {
+    let (tx, rx) = unbounded_channel();
+    let (too_slow, slow_rx) = oneshot::channel();
+    let writer = Writer {
+        rx,
+        queue: Queue::default(),
+        capacity,
+        max_behind,
+        too_slow: Some(too_slow),
+    };
+    (Outbox { tx }, writer, slow_rx)
+}
+
+impl Writer {
+    /// Write queued messages to `sink` until the outbox is closed and flushed
+    /// or the sink fails. `on_send` sees each message's size.
+    pub async fn run<S>(mut self, mut sink: S, on_send: impl Fn(usize)) -> OutboxStats
+    where
+        S: Sink<Message> + Unpin,
+    {
+        let mut open = true;
+        loop {
+            // Take everything already pushed first, so stale snapshots are
+            // dropped rather than written.
+            while open {
+                match self.rx.try_recv() {
+                    Ok(item) => open = self.accept(item),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => open = false,
+                }
+            }
+            let Some(msg) = self.queue.pop() else {
+                if !open {
+                    break;
+                }
+                match self.rx.recv().await {
+                    Some(item) => open = self.accept(item),
+                    None => open = false,
+                }
+                continue;
+            };
+
+            on_send(msg.len());
+            let send = sink.send(msg);
+            tokio::pin!(send);
+            // A slow socket keeps the write pending; keep queueing meanwhile.
+            let sent = loop {
+                tokio::select! {
+                    sent = &mut send => break sent.is_ok(),
+                    item = self.rx.recv(), if open => match item {
+                        Some(item) => open = self.accept(item),
+                        None => open = false,
+                    },
+                }
+            };
+            if !sent {
+                break;
+            }
+        }
+        self.queue.stats()
+    }
+
+    /// Queue one item; false once the outbox is closed.
+    fn accept(&mut self, item: Outgoing) -> bool {
+        if matches!(item, Outgoing::Close) {
+            return false;
+        }
+        self.queue.push(item, self.capacity);
+        if self.queue.behind_since.is_some() && self.queue.behind_for() >= self.max_behind {
+            if let Some(too_slow) = self.too_slow.take() {
+                let _ = too_slow.send(self.queue.stats());
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_all(writer: Writer) -> (Vec<Message>, OutboxStats) {
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let (tx, mut rx) = unbounded_channel();
+        let sink = futures_util::sink::unfold(tx, |tx, msg| async move {
+            let _ = tx.send(msg);
+            Ok::<_, ()>(tx)
+        });
+        let stats = rt.block_on(writer.run(Box::pin(sink), |_| {}));
+        let mut out = Vec::new();
+        while let Ok(msg) = rx.try_recv() {
+            out.push(msg);
+        }
+        (out, stats)
+    }
+
+    #[test]
+    fn old_snapshots_are_dropped_but_reliable_messages_are_not() {
+        let (outbox, writer, _) = channel(2, Duration::from_secs(5));
+        outbox.push(Message::Ping(vec![1]));
+        for i in 0..5u8 {
+            outbox.push_snapshot(vec![i]);
+        }
+        outbox.push(Message::Ping(vec![2]));
+        outbox.close();
+        // Nothing after the close goes out.
+        outbox.push(Message::Ping(vec![3]));
+
+        let (out, stats) = write_all(writer);
+        assert_eq!(
+            out,
+            vec![
+                Message::Ping(vec![1]),
+                Message::Binary(vec![3]),
+                Message::Binary(vec![4]),
+                Message::Ping(vec![2]),
+            ]
+        );
+        assert_eq!(stats, OutboxStats { depth: 0, peak: 4, dropped: 3 });
+    }
+
+    #[test]
+    fn catching_up_clears_the_behind_timer() {
+        let mut queue = Queue::default();
+        queue.push(Outgoing::Snapshot(vec![0]), 1);
+        queue.push(Outgoing::Snapshot(vec![1]), 1);
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(queue.behind_for() >= Duration::from_millis(5));
+
+        assert_eq!(queue.pop(), Some(Message::Binary(vec![1])));
+        assert_eq!(queue.behind_for(), Duration::ZERO);
+    }
+
+    #[test]
+    fn staying_behind_is_reported_once() {
+        let (outbox, writer, mut too_slow) = channel(1, Duration::ZERO);
+        outbox.push_snapshot(vec![0]);
+        outbox.push_snapshot(vec![1]);
+        outbox.push_snapshot(vec![2]);
+        outbox.close();
+
+        write_all(writer);
+        assert_eq!(too_slow.try_recv(), Ok(OutboxStats { depth: 1, peak: 1, dropped: 1 }));
+    }
+}
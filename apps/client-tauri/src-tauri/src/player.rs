@@ -1,51 +1,218 @@
 // apps/client-tauri/src-tauri/src/player.rs
 use serde::{Deserialize, Serialize};
-use crate::protocol::{Transform, TeamId};
+use tracing::{debug, info};
+use crate::entity::{EntityId, EntityStore, Health, Loadout, Score};
+use crate::protocol::{EntityKind, TeamId, Transform};
+use crate::session::Latency;
 
 pub const MAX_HEALTH: f32 = 100.0;
-pub const RESPAWN_TIME: f32 = 5.0;
+pub const MAX_STAMINA: f32 = 100.0;
+pub const STAMINA_DRAIN_PER_SEC: f32 = 20.0;
+pub const STAMINA_REGEN_PER_SEC: f32 = 12.5;
+// Once exhausted, stamina must climb back to this before sprint is allowed again.
+pub const STAMINA_RECOVER_THRESHOLD: f32 = 30.0;
+pub const MAX_NAME_LEN: usize = 16;
 
+/// Soldier-only state. Position, health, team, score and class live in the
+/// entity store's shared components.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
-    pub id: u32,
-    pub transform: Transform,
+    pub id: EntityId,
+    // Connection that controls this player; inputs are keyed by it.
+    pub conn_id: u32,
     pub velocity: (f32, f32, f32),
-    pub team: TeamId,
-    pub health: f32,
-    pub max_health: f32,
     pub is_dead: bool,
     pub respawn_timer: f32,
     pub fire_cooldown: f32, 
-    pub score_kills: u32,
-    pub score_deaths: u32,
-    pub class_id: u8,
+    pub stamina: f32,
+    pub max_stamina: f32,
+    pub is_exhausted: bool,
+    pub is_sprinting: bool,
+    // Vehicle this player is seated in, if any.
+    pub vehicle_id: Option<EntityId>,
+    // Last position that passed movement validation.
+    pub last_valid_pos: (f32, f32, f32),
+    pub movement_violations: u32,
+    // Tick of the last applied `StateUpdate` (client-authoritative movement).
+    pub last_update_tick: Option<u32>,
+    // Seconds since that update was applied.
+    pub since_update: f32,
+    // Time the update applied this tick covers; taken by validation.
+    pub update_window: Option<f32>,
+    // Seconds since validation last saw us on the ground (client-authoritative movement).
+    pub air_time: f32,
+    // Downward speed on the tick we touched the ground, consumed by the environment system.
+    pub landing_speed: Option<f32>,
+    // Who last damaged us, and for how much longer they get credit for our death.
+    pub last_attacker: Option<EntityId>,
+    pub last_attacker_timer: f32,
+    // Projectile weapons (grenades) have their own cooldown, and only fire
+    // once per `Fire` message tick.
+    pub throw_cooldown: f32,
+    pub last_throw_tick: Option<u32>,
+    // Round trip to this player's client, for lag-aware server logic.
+    #[serde(skip)]
+    pub latency: Latency,
 }
 
 impl Player {
-    pub fn new(id: u32, team: TeamId) -> Self {
+    pub fn new(id: EntityId, conn_id: u32) -> Self {
         Self {
             id,
-            transform: Transform { x: 0.0, y: 2.0, z: 0.0, yaw: 0.0, pitch: 0.0 },
+            conn_id,
             velocity: (0.0, 0.0, 0.0),
-            team,
-            health: MAX_HEALTH,
-            max_health: MAX_HEALTH,
             is_dead: false,
             respawn_timer: 0.0,
             fire_cooldown: 0.0,
-            score_kills: 0,
-            score_deaths: 0,
-            class_id: 0,
+            stamina: MAX_STAMINA,
+            max_stamina: MAX_STAMINA,
+            is_exhausted: false,
+            is_sprinting: false,
+            vehicle_id: None,
+            last_valid_pos: (0.0, 2.0, 0.0),
+            movement_violations: 0,
+            last_update_tick: None,
+            since_update: 0.0,
+            update_window: None,
+            air_time: 0.0,
+            landing_speed: None,
+            last_attacker: None,
+            last_attacker_timer: 0.0,
+            throw_cooldown: 0.0,
+            last_throw_tick: None,
+            latency: Latency::default(),
         }
     }
 
-    pub fn respawn(&mut self) {
-        self.health = self.max_health;
-        self.is_dead = false;
-        self.fire_cooldown = 0.0;
-        self.transform.x = 0.0;
-        self.transform.z = 0.0; 
-        self.transform.y = 2.0;
-        println!("[GAME] Player {} respawned", self.id);
+    /// Drain or regenerate stamina for this tick.
+    /// Returns whether the player is actually allowed to sprint this tick.
+    pub fn update_stamina(&mut self, wants_sprint: bool, dt: f32) -> bool {
+        let sprinting = wants_sprint && !self.is_exhausted && self.stamina > 0.0;
+
+        if sprinting {
+            self.stamina = (self.stamina - STAMINA_DRAIN_PER_SEC * dt).max(0.0);
+            if self.stamina <= 0.0 {
+                self.is_exhausted = true;
+                debug!(target: "movement", player = self.id, "exhausted");
+            }
+        } else {
+            self.stamina = (self.stamina + STAMINA_REGEN_PER_SEC * dt).min(self.max_stamina);
+            if self.is_exhausted && self.stamina >= STAMINA_RECOVER_THRESHOLD {
+                self.is_exhausted = false;
+            }
+        }
+
+        self.is_sprinting = sprinting;
+        sprinting
+    }
+}
+
+/// Clean up a client-supplied name: keep letters, digits and a few
+/// punctuation marks, collapse whitespace and cap the length.
+/// Returns None if nothing usable is left.
+pub fn sanitize_name(raw: &str) -> Option<String> {
+    let allowed: String = raw
+        .chars()
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .filter(|c| c.is_alphanumeric() || " -_.[]()".contains(*c))
+        .collect();
+    let name: String = allowed
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(MAX_NAME_LEN)
+        .collect();
+    let name = name.trim_end().to_string();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+impl EntityStore {
+    /// Create a soldier entity for a connection and put it at the spawn point.
+    pub fn spawn_player(&mut self, conn_id: u32, name: &str, team: TeamId) -> EntityId {
+        let id = self.spawn(EntityKind::Player);
+        self.insert_player(id, conn_id, team, Score::default(), Loadout::default());
+        self.set_name(id, name);
+        id
+    }
+
+    /// Sanitize and store a player's name, falling back to "Player <id>".
+    pub fn set_name(&mut self, id: EntityId, raw: &str) {
+        let name = sanitize_name(raw).unwrap_or_else(|| format!("Player {}", id));
+        self.names.insert(id, name);
+    }
+
+    /// Bring back a player who reconnected, under their old id, with their
+    /// team, score and loadout. They start at the spawn point.
+    pub fn restore_player(
+        &mut self,
+        id: EntityId,
+        conn_id: u32,
+        team: TeamId,
+        score: Score,
+        loadout: Loadout,
+    ) -> bool {
+        if !self.restore(id, EntityKind::Player) {
+            return false;
+        }
+        self.insert_player(id, conn_id, team, score, loadout);
+        true
     }
-}
\ No newline at end of file
+
+    fn insert_player(&mut self, id: EntityId, conn_id: u32, team: TeamId, score: Score, loadout: Loadout) {
+        self.transforms.insert(id, Transform { x: 0.0, y: 2.0, z: 0.0, yaw: 0.0, pitch: 0.0 });
+        self.healths.insert(id, Health::full(MAX_HEALTH));
+        self.teams.insert(id, team);
+        self.scores.insert(id, score);
+        self.loadouts.insert(id, loadout);
+        self.players.insert(id, Player::new(id, conn_id));
+        self.respawn_player(id);
+    }
+
+    pub fn respawn_player(&mut self, id: EntityId) {
+        let Some(player) = self.players.get_mut(&id) else { return };
+        player.is_dead = false;
+        player.fire_cooldown = 0.0;
+        player.throw_cooldown = 0.0;
+        player.stamina = player.max_stamina;
+        player.is_exhausted = false;
+        player.is_sprinting = false;
+        player.velocity = (0.0, 0.0, 0.0);
+        player.vehicle_id = None;
+        player.landing_speed = None;
+        player.air_time = 0.0;
+        player.last_attacker = None;
+        player.last_attacker_timer = 0.0;
+
+        if let Some(health) = self.healths.get_mut(&id) {
+            health.current = health.max;
+        }
+        if let Some(t) = self.transforms.get_mut(&id) {
+            t.x = 0.0;
+            t.z = 0.0;
+            t.y = 2.0;
+            player.last_valid_pos = (t.x, t.y, t.z);
+        }
+        info!(target: "combat", player = id, "respawned");
+    }
+
+    /// Mark the player dead and start a `respawn_time` countdown.
+    /// Kill credit is handled by the caller.
+    pub fn kill_player(&mut self, id: EntityId, respawn_time: f32) {
+        let Some(player) = self.players.get_mut(&id) else { return };
+        player.is_dead = true;
+        player.respawn_timer = respawn_time;
+        player.vehicle_id = None;
+
+        if let Some(health) = self.healths.get_mut(&id) {
+            health.current = 0.0;
+        }
+        if let Some(score) = self.scores.get_mut(&id) {
+            score.deaths += 1;
+        }
+    }
+}
@@ -0,0 +1,90 @@
+// apps/client-tauri/src-tauri/src/projectile.rs
+use serde::{Deserialize, Serialize};
+use crate::entity::{EntityId, EntityStore};
+use crate::protocol::{EntityKind, ProjectileKind, TeamId, Transform};
+
+// Weapon ids (in `ClientMessage::Fire`) that spawn projectiles instead of hitscan.
+pub const WEAPON_ID_GRENADE: u32 = 4;
+pub const WEAPON_ID_RIFLE_GRENADE: u32 = 5;
+
+impl ProjectileKind {
+    pub fn from_weapon_id(weapon_id: u32) -> Option<Self> {
+        match weapon_id {
+            WEAPON_ID_GRENADE => Some(ProjectileKind::Grenade),
+            WEAPON_ID_RIFLE_GRENADE => Some(ProjectileKind::RifleGrenade),
+            _ => None,
+        }
+    }
+
+    pub fn launch_speed(self) -> f32 {
+        match self {
+            ProjectileKind::Grenade => 15.0,
+            ProjectileKind::RifleGrenade => 35.0,
+        }
+    }
+
+    pub fn fuse(self) -> f32 {
+        match self {
+            ProjectileKind::Grenade => 3.0,
+            ProjectileKind::RifleGrenade => 1.5,
+        }
+    }
+
+    // Fraction of speed kept along the surface normal after a bounce.
+    pub fn restitution(self) -> f32 {
+        match self {
+            ProjectileKind::Grenade => 0.45,
+            ProjectileKind::RifleGrenade => 0.25,
+        }
+    }
+
+    pub fn max_damage(self) -> f32 {
+        match self {
+            ProjectileKind::Grenade => 110.0,
+            ProjectileKind::RifleGrenade => 90.0,
+        }
+    }
+
+    pub fn blast_radius(self) -> f32 {
+        match self {
+            ProjectileKind::Grenade => 7.0,
+            ProjectileKind::RifleGrenade => 5.0,
+        }
+    }
+
+    // Seconds before the same player can fire this kind again.
+    pub fn cooldown(self) -> f32 {
+        match self {
+            ProjectileKind::Grenade => 1.0,
+            ProjectileKind::RifleGrenade => 1.5,
+        }
+    }
+}
+
+/// Projectile-only state. Position and team live in the entity store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Projectile {
+    pub id: EntityId,
+    pub kind: ProjectileKind,
+    pub owner: EntityId,
+    pub velocity: (f32, f32, f32),
+    pub fuse: f32,
+}
+
+impl EntityStore {
+    pub fn spawn_projectile(
+        &mut self,
+        kind: ProjectileKind,
+        owner: EntityId,
+        team: TeamId,
+        position: (f32, f32, f32),
+        velocity: (f32, f32, f32),
+    ) -> EntityId {
+        let id = self.spawn(EntityKind::Projectile);
+        let (x, y, z) = position;
+        self.transforms.insert(id, Transform { x, y, z, yaw: 0.0, pitch: 0.0 });
+        self.teams.insert(id, team);
+        self.projectiles.insert(id, Projectile { id, kind, owner, velocity, fuse: kind.fuse() });
+        id
+    }
+}
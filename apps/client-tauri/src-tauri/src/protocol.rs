@@ -1,10 +1,42 @@
 // apps/client-tauri/src-tauri/src/protocol.rs
 use serde::{Deserialize, Serialize};
 
+// Bumped whenever the wire format changes. Clients must send the same
+// version in `ClientMessage::Hello` or they are turned away.
+pub const PROTOCOL_VERSION: u32 = 3;
+
 #[derive(Serialize, Deserialize)]
 pub struct ServerEnvelope {
+    // Entity id of the receiving client's player.
     pub your_id: u32,
-    pub snapshot: TickSnapshot,
+    // Full snapshot: on join, and whenever the client has no acked baseline.
+    pub snapshot: Option<TickSnapshot>,
+    // Only on the first envelope after joining. Reconnect with
+    // `?session=<token>` to reclaim the same player.
+    #[serde(default)]
+    pub session_token: Option<String>,
+    // Also first envelope only, when the server has UDP enabled. Send it in a
+    // UDP `Bind` packet to this port to get snapshots over UDP.
+    #[serde(default)]
+    pub udp_port: Option<u16>,
+    #[serde(default)]
+    pub udp_token: Option<String>,
+    // Otherwise the changes since the client's last `SnapshotAck`.
+    #[serde(default)]
+    pub delta: Option<SnapshotDelta>,
+    // First envelope only: set if the server accepted `Hello { compact }`.
+    // Every later envelope is then in the compact encoding (see compact.rs).
+    #[serde(default)]
+    pub compact: Option<CompactFormat>,
+}
+
+// Parameters of the compact encoding. Positions are sent as centimetres
+// from this corner of the map.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CompactFormat {
+    pub origin_x: f32,
+    pub origin_y: f32,
+    pub origin_z: f32,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -14,7 +46,30 @@ pub enum TeamId {
     TeamB,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ProjectileKind {
+    // Thrown by hand: slow, bouncy, long fuse.
+    Grenade,
+    // Launched from a rifle: fast, flat, short fuse.
+    RifleGrenade,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum VehicleKind {
+    Jeep,
+    Tank,
+}
+
+// What an entity in the snapshot is, so clients know how to render it.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum EntityKind {
+    #[default]
+    Player,
+    Vehicle,
+    Projectile,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Transform {
     pub x: f32,
     pub y: f32,
@@ -32,6 +87,8 @@ pub struct ClientAxes {
     pub jump: bool,
     pub shoot: bool,
     pub reload: bool,
+    #[serde(default)]
+    pub sprint: bool,
     pub yaw: f32,
     pub pitch: f32,
 }
@@ -46,12 +103,40 @@ pub struct Vec3 {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ClientMessage {
+    // Must be the first message on a new connection.
+    #[serde(rename = "hello")]
+    Hello {
+        #[serde(rename = "protocolVersion")]
+        protocol_version: u32,
+        name: String,
+        // None = let the server balance teams.
+        #[serde(rename = "requestedTeam", default)]
+        requested_team: Option<TeamId>,
+        // Client can decode the compact snapshot encoding.
+        #[serde(default)]
+        compact: bool,
+    },
+
     #[serde(rename = "input")]
     Input {
         tick: u32,
         axes: ClientAxes,
     },
 
+    // Client-authoritative movement: the client reports its own transform.
+    #[serde(rename = "update")]
+    StateUpdate {
+        tick: u32,
+        x: f32,
+        y: f32,
+        z: f32,
+        yaw: f32,
+        #[serde(default)]
+        pitch: f32,
+        #[serde(default)]
+        sprint: bool,
+    },
+
     #[serde(rename = "fire")]
     Fire {
         tick: u32,
@@ -61,16 +146,35 @@ pub enum ClientMessage {
         weapon_id: u32,
     },
 
+    #[serde(rename = "enter_vehicle")]
+    EnterVehicle {
+        #[serde(rename = "vehicleId")]
+        vehicle_id: u32,
+        // None = first free seat.
+        #[serde(default)]
+        seat: Option<u8>,
+    },
+
+    #[serde(rename = "exit_vehicle")]
+    ExitVehicle,
+
     #[serde(rename = "spawn_request")]
     SpawnRequest {
         #[serde(rename = "classId")]
         class_id: u32,
     },
+
+    // Newest snapshot tick the client holds, used as the baseline for deltas.
+    // Ack 0 to ask for a full snapshot.
+    #[serde(rename = "ack")]
+    SnapshotAck {
+        tick: u64,
+    },
 }
 
 // ---------- SERVER → CLIENT (SNAPSHOT) ----------
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EntitySnapshot {
     pub eid: u32,
     pub transform: Transform,
@@ -79,37 +183,49 @@ pub struct EntitySnapshot {
     pub team: Option<TeamStruct>,
     pub score: Option<ScoreStruct>,
     pub loadout: Option<LoadoutStruct>,
+    #[serde(default)]
+    pub kind: EntityKind,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub latency: Option<LatencyStruct>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct HealthStruct {
     pub current: f32,
     pub max: f32,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StaminaStruct {
     pub current: f32,
     pub max: f32,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TeamStruct {
     pub id: TeamId,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ScoreStruct {
     pub kills: u32,
     pub deaths: u32,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LoadoutStruct {
     pub class_id: u8,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatencyStruct {
+    pub rtt_ms: f32,
+    pub jitter_ms: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GameModeState {
     pub team_a_tickets: i32,
     pub team_b_tickets: i32,
@@ -117,7 +233,7 @@ pub struct GameModeState {
     pub winner: TeamId,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FlagSnapshot {
     pub id: u32,
     pub x: f32,
@@ -128,9 +244,96 @@ pub struct FlagSnapshot {
     pub capture: f32,
 }
 
-#[derive(Serialize, Deserialize)]
+// Projectile-only state; the position is in the matching entity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectileSnapshot {
+    pub id: u32,
+    pub kind: ProjectileKind,
+    pub owner: u32,
+    pub vx: f32,
+    pub vy: f32,
+    pub vz: f32,
+}
+
+// Vehicle-only state; transform and health are in the matching entity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VehicleSnapshot {
+    pub id: u32,
+    pub kind: VehicleKind,
+    // Occupant entity id per seat; seat 0 is the driver.
+    pub seats: Vec<Option<u32>>,
+    pub destroyed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreboardEntry {
+    pub eid: u32,
+    pub name: String,
+    pub team: TeamId,
+    pub kills: u32,
+    pub deaths: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TickSnapshot {
     pub entities: Vec<EntitySnapshot>,
     pub flags: Vec<FlagSnapshot>,
     pub game_state: GameModeState,
+    #[serde(default)]
+    pub projectiles: Vec<ProjectileSnapshot>,
+    #[serde(default)]
+    pub vehicles: Vec<VehicleSnapshot>,
+    // Players sorted by kills, then fewest deaths.
+    #[serde(default)]
+    pub scoreboard: Vec<ScoreboardEntry>,
+    #[serde(default)]
+    pub tick: u64,
+}
+
+// ---------- SERVER → CLIENT (DELTA) ----------
+
+// Bits for `EntityDelta::cleared`.
+pub const CLEARED_HEALTH: u16 = 1 << 0;
+pub const CLEARED_STAMINA: u16 = 1 << 1;
+pub const CLEARED_TEAM: u16 = 1 << 2;
+pub const CLEARED_SCORE: u16 = 1 << 3;
+pub const CLEARED_LOADOUT: u16 = 1 << 4;
+pub const CLEARED_NAME: u16 = 1 << 5;
+pub const CLEARED_LATENCY: u16 = 1 << 6;
+
+// Changed fields of an entity present in both snapshots. None = unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntityDelta {
+    pub eid: u32,
+    pub transform: Option<Transform>,
+    pub health: Option<HealthStruct>,
+    pub stamina: Option<StaminaStruct>,
+    pub team: Option<TeamStruct>,
+    pub score: Option<ScoreStruct>,
+    pub loadout: Option<LoadoutStruct>,
+    pub kind: Option<EntityKind>,
+    pub name: Option<String>,
+    pub latency: Option<LatencyStruct>,
+    // Optional components that went away, as `CLEARED_*` bits.
+    pub cleared: u16,
+}
+
+// `TickSnapshot` as changes against the snapshot with tick `baseline`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotDelta {
+    pub baseline: u64,
+    pub tick: u64,
+    // New entities in full.
+    pub spawned: Vec<EntitySnapshot>,
+    pub changed: Vec<EntityDelta>,
+    pub removed: Vec<u32>,
+    // Flags that changed, in full.
+    pub flags: Vec<FlagSnapshot>,
+    pub game_state: Option<GameModeState>,
+    // New or changed, in full.
+    pub projectiles: Vec<ProjectileSnapshot>,
+    pub removed_projectiles: Vec<u32>,
+    pub vehicles: Vec<VehicleSnapshot>,
+    pub removed_vehicles: Vec<u32>,
+    pub scoreboard: Option<Vec<ScoreboardEntry>>,
 }
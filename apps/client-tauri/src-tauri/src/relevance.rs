@@ -0,0 +1,294 @@
+// apps/client-tauri/src-tauri/src/relevance.rs
+use std::collections::{HashMap, HashSet};
+
+use crate::protocol::{EntityKind, EntitySnapshot, TeamId, TickSnapshot};
+use crate::sim::Collider;
+use crate::systems::projectiles::line_of_sight;
+
+// Same eye offset as hitscan in combat.rs.
+const EYE_HEIGHT: f32 = 0.6;
+// Vehicles are big and loud, so they carry further.
+const VEHICLE_RANGE_FACTOR: f32 = 1.5;
+
+/// Which entities a client gets told about.
+#[derive(Debug, Clone, Copy)]
+pub struct RelevanceConfig {
+    // Enemies, vehicles and projectiles further away than this are left out.
+    pub max_distance: f32,
+    // Enemies this close are sent even without line of sight, so they don't
+    // pop in when they step round a corner.
+    pub always_distance: f32,
+    // Inside this distance entities update every snapshot; further out they
+    // update proportionally less often.
+    pub full_rate_distance: f32,
+    // Snapshots an enemy stays relevant after it was last seen.
+    pub visibility_grace: u64,
+    // Most non-team entities updated in one snapshot.
+    pub budget: usize,
+}
+
+impl Default for RelevanceConfig {
+    fn default() -> Self {
+        Self {
+            max_distance: 200.0,
+            always_distance: 10.0,
+            full_rate_distance: 50.0,
+            visibility_grace: 10,
+            budget: 32,
+        }
+    }
+}
+
+/// Per-client relevance state.
+#[derive(Default)]
+pub struct Interest {
+    // Builds up each snapshot an entity is relevant, spent when it is sent.
+    priority: HashMap<u32, f32>,
+    // Tick an enemy was last in line of sight.
+    last_seen: HashMap<u32, u64>,
+    // Entities skipped by the scheduler repeat their last sent state.
+    last_sent: HashMap<u32, EntitySnapshot>,
+}
+
+impl Interest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The part of `snapshot` that `viewer` should see. Teammates and the
+    /// viewer are always included and always fresh. Everything else must be
+    /// in range (and, for enemy players, in sight) and is updated at a rate
+    /// that falls off with distance.
+    pub fn filter(
+        &mut self,
+        snapshot: &TickSnapshot,
+        viewer: u32,
+        colliders: &[Collider],
+        config: &RelevanceConfig,
+    ) -> TickSnapshot {
+        let Some(me) = snapshot.entities.iter().find(|e| e.eid == viewer) else {
+            return snapshot.clone();
+        };
+        let my_team = me.team.as_ref().map_or(TeamId::None, |t| t.id);
+        let origin = &me.transform;
+        let eye = (origin.x, origin.y + EYE_HEIGHT, origin.z);
+
+        let mut entities: Vec<EntitySnapshot> = Vec::new();
+        let mut candidates: Vec<(f32, &EntitySnapshot)> = Vec::new();
+        let mut relevant: HashSet<u32> = HashSet::new();
+
+        for e in &snapshot.entities {
+            let team = e.team.as_ref().map_or(TeamId::None, |t| t.id);
+            if e.eid == viewer || (my_team != TeamId::None && team == my_team) {
+                entities.push(e.clone());
+                continue;
+            }
+
+            let t = &e.transform;
+            let (dx, dy, dz) = (t.x - origin.x, t.y - origin.y, t.z - origin.z);
+            let distance = (dx * dx + dy * dy + dz * dz).sqrt();
+            let max = match e.kind {
+                EntityKind::Vehicle => config.max_distance * VEHICLE_RANGE_FACTOR,
+                _ => config.max_distance,
+            };
+            if distance > max {
+                continue;
+            }
+
+            if e.kind == EntityKind::Player && distance > config.always_distance {
+                if line_of_sight(colliders, eye, (t.x, t.y + EYE_HEIGHT, t.z)) {
+                    self.last_seen.insert(e.eid, snapshot.tick);
+                }
+                let recently_seen = self
+                    .last_seen
+                    .get(&e.eid)
+                    .is_some_and(|seen| snapshot.tick.saturating_sub(*seen) <= config.visibility_grace);
+                if !recently_seen {
+                    continue;
+                }
+            }
+
+            let weight = if distance <= config.full_rate_distance {
+                1.0
+            } else {
+                config.full_rate_distance / distance
+            };
+            let priority = self.priority.entry(e.eid).or_insert(0.0);
+            *priority += weight;
+            candidates.push((*priority, e));
+            relevant.insert(e.eid);
+        }
+
+        // Most overdue first; whatever misses the budget keeps its priority.
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+        for (i, (priority, e)) in candidates.into_iter().enumerate() {
+            if priority >= 1.0 && i < config.budget {
+                self.priority.insert(e.eid, 0.0);
+                self.last_sent.insert(e.eid, e.clone());
+                entities.push(e.clone());
+            } else if let Some(stale) = self.last_sent.get(&e.eid) {
+                entities.push(stale.clone());
+            }
+        }
+        entities.sort_by_key(|e| e.eid);
+
+        self.priority.retain(|id, _| relevant.contains(id));
+        self.last_sent.retain(|id, _| relevant.contains(id));
+        self.last_seen
+            .retain(|_, seen| snapshot.tick.saturating_sub(*seen) <= config.visibility_grace);
+
+        let included: HashSet<u32> = entities.iter().map(|e| e.eid).collect();
+        TickSnapshot {
+            entities,
+            flags: snapshot.flags.clone(),
+            game_state: snapshot.game_state.clone(),
+            projectiles: snapshot
+                .projectiles
+                .iter()
+                .filter(|p| included.contains(&p.id))
+                .cloned()
+                .collect(),
+            vehicles: snapshot
+                .vehicles
+                .iter()
+                .filter(|v| included.contains(&v.id))
+                .cloned()
+                .collect(),
+            scoreboard: snapshot.scoreboard.clone(),
+            tick: snapshot.tick,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{GameModeState, TeamStruct, Transform};
+
+    fn player(eid: u32, team: TeamId, x: f32, z: f32) -> EntitySnapshot {
+        EntitySnapshot {
+            eid,
+            transform: Transform { x, y: 0.0, z, yaw: 0.0, pitch: 0.0 },
+            health: None,
+            stamina: None,
+            team: Some(TeamStruct { id: team }),
+            score: None,
+            loadout: None,
+            kind: EntityKind::Player,
+            name: None,
+            latency: None,
+        }
+    }
+
+    fn snapshot(tick: u64, entities: Vec<EntitySnapshot>) -> TickSnapshot {
+        TickSnapshot {
+            entities,
+            flags: Vec::new(),
+            game_state: GameModeState {
+                team_a_tickets: 100,
+                team_b_tickets: 100,
+                match_ended: false,
+                winner: TeamId::None,
+            },
+            projectiles: Vec::new(),
+            vehicles: Vec::new(),
+            scoreboard: Vec::new(),
+            tick,
+        }
+    }
+
+    fn ids(s: &TickSnapshot) -> Vec<u32> {
+        s.entities.iter().map(|e| e.eid).collect()
+    }
+
+    #[test]
+    fn far_enemies_are_dropped_but_teammates_are_not() {
+        let config = RelevanceConfig::default();
+        let s = snapshot(
+            1,
+            vec![
+                player(1, TeamId::TeamA, 0.0, 0.0),
+                player(2, TeamId::TeamA, 500.0, 0.0),
+                player(3, TeamId::TeamB, 500.0, 0.0),
+                player(4, TeamId::TeamB, 20.0, 0.0),
+            ],
+        );
+        let out = Interest::new().filter(&s, 1, &[], &config);
+        assert_eq!(ids(&out), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn enemies_behind_walls_are_hidden() {
+        let config = RelevanceConfig::default();
+        let walls = [Collider { min_x: 20.0, min_y: -1.0, min_z: -5.0, max_x: 21.0, max_y: 5.0, max_z: 5.0 }];
+        let s = snapshot(
+            1,
+            vec![
+                player(1, TeamId::TeamA, 0.0, 0.0),
+                // Behind the wall.
+                player(2, TeamId::TeamB, 40.0, 0.0),
+                // In the open.
+                player(3, TeamId::TeamB, 0.0, 40.0),
+                // Behind the wall but close enough to hear.
+                player(4, TeamId::TeamB, 8.0, 0.0),
+            ],
+        );
+        let mut interest = Interest::new();
+        assert_eq!(ids(&interest.filter(&s, 1, &walls, &config)), vec![1, 3, 4]);
+
+        // Ducking behind the wall keeps an enemy around for the grace period only.
+        let mut s = s;
+        s.entities[2].transform.x = 40.0;
+        s.entities[2].transform.z = 0.0;
+        s.tick = 1 + config.visibility_grace;
+        assert!(ids(&interest.filter(&s, 1, &walls, &config)).contains(&3));
+        s.tick += 1;
+        assert!(!ids(&interest.filter(&s, 1, &walls, &config)).contains(&3));
+    }
+
+    #[test]
+    fn distant_entities_update_less_often() {
+        let config = RelevanceConfig::default();
+        let mut interest = Interest::new();
+        let mut fresh = 0;
+        for tick in 1..=20 {
+            // 100m away: half the full rate.
+            let mut far = player(2, TeamId::TeamB, 100.0, 0.0);
+            far.transform.yaw = tick as f32;
+            let s = snapshot(tick, vec![player(1, TeamId::TeamA, 0.0, 0.0), far]);
+            let out = interest.filter(&s, 1, &[], &config);
+            let sent = out.entities.iter().find(|e| e.eid == 2);
+            match sent {
+                Some(e) if e.transform.yaw == tick as f32 => fresh += 1,
+                // Skipped snapshots repeat the last sent state.
+                Some(_) => {}
+                None => assert_eq!(tick, 1, "only missing before its first send"),
+            }
+        }
+        assert_eq!(fresh, 10);
+    }
+
+    #[test]
+    fn budget_spreads_updates_across_snapshots() {
+        let config = RelevanceConfig { budget: 2, ..Default::default() };
+        let mut interest = Interest::new();
+        let mut entities = vec![player(1, TeamId::TeamA, 0.0, 0.0)];
+        entities.extend((2..=5).map(|eid| player(eid, TeamId::TeamB, 0.0, eid as f32 * 3.0)));
+
+        let mut updates: HashMap<u32, u32> = HashMap::new();
+        for tick in 1..=8 {
+            let mut s = snapshot(tick, entities.clone());
+            for e in s.entities.iter_mut() {
+                e.transform.x = tick as f32;
+            }
+            let out = interest.filter(&s, 1, &[], &config);
+            for e in out.entities.iter().filter(|e| e.eid != 1 && e.transform.x == tick as f32) {
+                *updates.entry(e.eid).or_default() += 1;
+            }
+            assert!(out.entities.len() <= 5);
+        }
+        // 2 updates a tick over 8 ticks, shared fairly between 4 enemies.
+        assert_eq!(updates.values().sum::<u32>(), 16);
+        assert!(updates.values().all(|n| *n >= 3), "{:?}", updates);
+    }
+}
@@ -0,0 +1,136 @@
+// apps/client-tauri/src-tauri/src/session.rs
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::entity::{EntityId, Loadout, Score};
+use crate::protocol::TeamId;
+
+// How long a dropped client can come back and reclaim its player.
+pub const RECONNECT_GRACE: Duration = Duration::from_secs(60);
+
+// How many recent disconnects SimState keeps around for inspection.
+pub const MAX_DISCONNECT_LOG: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisconnectReason {
+    // Close frame from the client.
+    ClientClosed,
+    // Stopped answering pings or stopped sending messages.
+    Timeout,
+    // Removed by the server, e.g. for idling.
+    Kicked(String),
+    // Couldn't receive snapshots as fast as the server sends them.
+    TooSlow,
+    // The host stopped the server.
+    ServerShutdown,
+    // Socket error or the connection dropped without a close frame.
+    Error(String),
+}
+
+impl fmt::Display for DisconnectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisconnectReason::ClientClosed => write!(f, "client closed"),
+            DisconnectReason::Timeout => write!(f, "timed out"),
+            DisconnectReason::Kicked(why) => write!(f, "kicked: {}", why),
+            DisconnectReason::TooSlow => write!(f, "fell too far behind"),
+            DisconnectReason::ServerShutdown => write!(f, "server shutting down"),
+            DisconnectReason::Error(e) => write!(f, "error: {}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DisconnectRecord {
+    pub conn_id: u32,
+    pub eid: EntityId,
+    pub reason: DisconnectReason,
+    pub frame: u64,
+}
+
+/// Smoothed round-trip time and jitter for one connection, measured with
+/// WebSocket pings. Same smoothing as TCP's RTO estimator (RFC 6298).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Latency {
+    pub rtt_ms: f32,
+    // Mean deviation of the RTT samples.
+    pub jitter_ms: f32,
+    pub samples: u32,
+}
+
+impl Latency {
+    pub fn add_sample(&mut self, rtt: Duration) {
+        let sample = rtt.as_secs_f32() * 1000.0;
+        if self.samples == 0 {
+            self.rtt_ms = sample;
+            self.jitter_ms = sample / 2.0;
+        } else {
+            self.jitter_ms += ((self.rtt_ms - sample).abs() - self.jitter_ms) / 4.0;
+            self.rtt_ms += (sample - self.rtt_ms) / 8.0;
+        }
+        self.samples = self.samples.saturating_add(1);
+    }
+}
+
+/// What a player keeps while their client is away.
+#[derive(Debug, Clone, Copy)]
+pub struct SavedPlayer {
+    pub team: TeamId,
+    pub score: Score,
+    pub loadout: Loadout,
+    pub disconnected_at: Instant,
+}
+
+/// One client's seat in the match, identified by the token it was given on join.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub eid: EntityId,
+    // Live connection, or None while the client is away.
+    pub conn_id: Option<u32>,
+    // Set while the client is away.
+    pub saved: Option<SavedPlayer>,
+}
+
+impl Session {
+    pub fn is_expired(&self, now: Instant) -> bool {
+        self.saved
+            .is_some_and(|s| now.duration_since(s.disconnected_at) >= RECONNECT_GRACE)
+    }
+}
+
+/// Random 128-bit hex token from the OS random number generator. Session
+/// and UDP tokens are credentials: whoever holds one can take over the
+/// player or its UDP channel.
+pub fn new_token() -> String {
+    let mut bytes = [0u8; 16];
+    // Without OS randomness there is no safe fallback.
+    getrandom::getrandom(&mut bytes).expect("OS random number generator unavailable");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_unique_hex() {
+        let tokens: std::collections::HashSet<String> = (0..100).map(|_| new_token()).collect();
+        assert_eq!(tokens.len(), 100);
+        assert!(tokens.iter().all(|t| t.len() == 32 && t.chars().all(|c| c.is_ascii_hexdigit())));
+    }
+
+    #[test]
+    fn latency_follows_rfc_6298_smoothing() {
+        let mut latency = Latency::default();
+        let mut add = |ms| {
+            latency.add_sample(Duration::from_millis(ms));
+            (latency.rtt_ms, latency.jitter_ms)
+        };
+        // First sample: RTT as measured, jitter half of it.
+        assert_eq!(add(100), (100.0, 50.0));
+        // Then RTT moves 1/8 and jitter 1/4 of the way to each new sample.
+        assert_eq!(add(120), (102.5, 42.5));
+        assert_eq!(add(80), (99.6875, 37.5));
+        assert_eq!(latency.samples, 3);
+    }
+}
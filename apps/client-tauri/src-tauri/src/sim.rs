@@ -1,5 +1,7 @@
 // apps/client-tauri/src-tauri/src/sim.rs
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use tracing::{info, trace};
 use crate::protocol::{
     TickSnapshot,
     EntitySnapshot,
@@ -12,16 +14,51 @@ use crate::protocol::{
     ClientMessage,
     LoadoutStruct,
     FlagSnapshot,
+    ProjectileSnapshot,
+    VehicleSnapshot,
+    ScoreboardEntry,
+    LatencyStruct,
 };
+use crate::config::{Rules, ServerConfig};
+use crate::entity::{EntityId, EntityStore};
+use crate::session::{self, DisconnectReason, DisconnectRecord, SavedPlayer, Session, MAX_DISCONNECT_LOG};
 use crate::systems;
-use crate::player::Player;
+use crate::systems::movement::MovementAuthority;
+use crate::systems::validation::MovementViolation;
+use crate::vehicle::{VehicleRequest, VehicleSpawn};
 
 pub struct SimState {
-    pub players: HashMap<u32, Player>,
+    // Players, vehicles and projectiles.
+    pub entities: EntityStore,
+    // Connection id -> the player entity it controls.
+    pub connections: HashMap<u32, EntityId>,
+    // Next connection id to hand out. Only ever increases, so a new
+    // connection can never take over a live one's id.
+    next_conn_id: u32,
+    // Session token -> session, kept for a grace period after a disconnect
+    // so the client can reclaim its player.
+    pub sessions: HashMap<String, Session>,
+    // Most recent disconnects and why they happened, oldest first.
+    pub disconnects: VecDeque<DisconnectRecord>,
     pub tickets_a: f32,
     pub tickets_b: f32,
     pub frame_count: u64,
     pub flags: Vec<FlagZone>,
+    pub bounds: WorldBounds,
+    pub kill_volumes: Vec<KillVolume>,
+    pub colliders: Vec<Collider>,
+    pub vehicle_spawns: Vec<VehicleSpawn>,
+    // Enter/exit requests received since the last tick, by player entity.
+    pub vehicle_requests: Vec<(EntityId, VehicleRequest)>,
+    // Input-driven (server integrates) vs client-reported movement.
+    pub movement_authority: MovementAuthority,
+    // Most recent movement validation failures, oldest first.
+    pub movement_violations: VecDeque<MovementViolation>,
+    // Gameplay tunables and friendly fire.
+    pub rules: Rules,
+    // New joins are refused once this many sessions (live or in their
+    // reconnect grace period) exist.
+    pub max_players: usize,
 }
 
 // Simple server-side representation of a Conquest flag.
@@ -40,28 +77,229 @@ pub struct FlagZone {
     pub capture: f32,
 }
 
+// Axis-aligned box that kills anyone who enters it (pits, water, map holes).
+pub struct KillVolume {
+    pub id: u32,
+    pub min_x: f32,
+    pub min_y: f32,
+    pub min_z: f32,
+    pub max_x: f32,
+    pub max_y: f32,
+    pub max_z: f32,
+}
+
+// Solid axis-aligned box of map geometry. Projectiles bounce off it and it
+// blocks explosion line of sight.
+#[derive(Debug, Clone)]
+pub struct Collider {
+    pub min_x: f32,
+    pub min_y: f32,
+    pub min_z: f32,
+    pub max_x: f32,
+    pub max_y: f32,
+    pub max_z: f32,
+}
+
+// Playable area of a map. Leaving it (including falling below `min_y`) is fatal.
+pub struct WorldBounds {
+    pub min_x: f32,
+    pub min_y: f32,
+    pub min_z: f32,
+    pub max_x: f32,
+    pub max_y: f32,
+    pub max_z: f32,
+}
+
 impl SimState {
     pub fn new() -> Self {
+        Self::with_config(&ServerConfig::default())
+    }
+
+    /// Set up the match described by an already validated config.
+    pub fn with_config(config: &ServerConfig) -> Self {
+        // Warehouse is the only map so far; validation rejects any other name.
+        let vehicle_spawns = crate::maps::warehouse::create_vehicle_spawns();
+        let mut entities = EntityStore::new();
+        for spawn in &vehicle_spawns {
+            entities.spawn_vehicle(spawn);
+        }
         Self {
-            players: HashMap::new(),
-            tickets_a: 100.0,
-            tickets_b: 100.0,
+            entities,
+            connections: HashMap::new(),
+            next_conn_id: 1,
+            sessions: HashMap::new(),
+            disconnects: VecDeque::new(),
+            tickets_a: config.game.tickets as f32,
+            tickets_b: config.game.tickets as f32,
             frame_count: 0,
             flags: crate::maps::warehouse::create_flags(),
+            bounds: crate::maps::warehouse::world_bounds(),
+            kill_volumes: crate::maps::warehouse::create_kill_volumes(),
+            colliders: crate::maps::warehouse::create_colliders(),
+            vehicle_spawns,
+            vehicle_requests: Vec::new(),
+            movement_authority: config.net.movement,
+            movement_violations: VecDeque::new(),
+            rules: config.rules.clone(),
+            max_players: config.server.max_players as usize,
+        }
+    }
+
+    /// Reserve an id for a new connection. Ids are never reused.
+    pub fn allocate_connection_id(&mut self) -> u32 {
+        let id = self.next_conn_id;
+        self.next_conn_id += 1;
+        id
+    }
+
+    /// Spawn a player entity for a new connection and return its entity id.
+    pub fn handle_join(
+        &mut self,
+        conn_id: u32,
+        name: &str,
+        requested_team: Option<TeamId>,
+    ) -> Result<EntityId, String> {
+        if let Some(eid) = self.connections.get(&conn_id) {
+            return Err(format!(
+                "connection {} already joined as Player {}",
+                conn_id, eid
+            ));
+        }
+
+        if self.sessions.len() >= self.max_players {
+            return Err(format!("server is full ({} players)", self.max_players));
+        }
+
+        let team = self.pick_team(requested_team);
+        let eid = self.entities.spawn_player(conn_id, name, team);
+        self.connections.insert(conn_id, eid);
+        self.sessions.insert(
+            session::new_token(),
+            Session { eid, conn_id: Some(conn_id), saved: None },
+        );
+        info!(
+            target: "net",
+            conn = conn_id,
+            player = eid,
+            name = %self.entities.names[&eid],
+            ?team,
+            "player joined"
+        );
+        Ok(eid)
+    }
+
+    /// Honor a team request unless it would leave that team two or more
+    /// players ahead; otherwise put the player on the smaller team.
+    fn pick_team(&self, requested: Option<TeamId>) -> TeamId {
+        let count = |team: TeamId| self.entities.teams.iter()
+            .filter(|(id, t)| **t == team && self.entities.players.contains_key(id))
+            .count();
+        let (a, b) = (count(TeamId::TeamA), count(TeamId::TeamB));
+
+        match requested {
+            Some(TeamId::TeamA) if a <= b => TeamId::TeamA,
+            Some(TeamId::TeamB) if b <= a => TeamId::TeamB,
+            _ if b < a => TeamId::TeamB,
+            _ => TeamId::TeamA,
+        }
+    }
+
+    /// Reclaim a player left behind by a dropped connection, with its old
+    /// entity id, team, score and loadout.
+    pub fn resume_session(
+        &mut self,
+        conn_id: u32,
+        token: &str,
+        name: &str,
+    ) -> Result<EntityId, String> {
+        if self.connections.contains_key(&conn_id) {
+            return Err(format!("connection {} already joined", conn_id));
+        }
+        let session = self
+            .sessions
+            .get_mut(token)
+            .ok_or_else(|| "unknown or expired session".to_string())?;
+        let Some(saved) = session.saved else {
+            return Err(format!("Player {} is still connected", session.eid));
+        };
+
+        let eid = session.eid;
+        if !self
+            .entities
+            .restore_player(eid, conn_id, saved.team, saved.score, saved.loadout)
+        {
+            return Err(format!("Player {} could not be restored", eid));
+        }
+        session.conn_id = Some(conn_id);
+        session.saved = None;
+        self.entities.set_name(eid, name);
+        self.connections.insert(conn_id, eid);
+        info!(target: "net", conn = conn_id, player = eid, "session resumed");
+        Ok(eid)
+    }
+
+    /// Token a connection can use to resume its session after a drop.
+    pub fn session_token(&self, conn_id: u32) -> Option<&str> {
+        self.sessions
+            .iter()
+            .find(|(_, s)| s.conn_id == Some(conn_id))
+            .map(|(token, _)| token.as_str())
+    }
+
+    pub fn queue_vehicle_request(&mut self, conn_id: u32, request: VehicleRequest) {
+        if let Some(eid) = self.connections.get(&conn_id) {
+            self.vehicle_requests.push((*eid, request));
+        }
+    }
+
+    pub fn handle_disconnect(&mut self, conn_id: u32, reason: DisconnectReason) {
+        let Some(eid) = self.connections.remove(&conn_id) else { return };
+
+        // Keep what the player earned so they can pick it back up.
+        if let Some(session) = self.sessions.values_mut().find(|s| s.conn_id == Some(conn_id)) {
+            session.conn_id = None;
+            session.saved = Some(SavedPlayer {
+                team: self.entities.teams.get(&eid).copied().unwrap_or(TeamId::None),
+                score: self.entities.scores.get(&eid).copied().unwrap_or_default(),
+                loadout: self.entities.loadouts.get(&eid).copied().unwrap_or_default(),
+                disconnected_at: Instant::now(),
+            });
         }
+        self.entities.despawn(eid);
+
+        let record = DisconnectRecord { conn_id, eid, reason, frame: self.frame_count };
+        info!(
+            target: "net",
+            conn = record.conn_id,
+            player = record.eid,
+            reason = %record.reason,
+            frame = record.frame,
+            "player left"
+        );
+        if self.disconnects.len() >= MAX_DISCONNECT_LOG {
+            self.disconnects.pop_front();
+        }
+        self.disconnects.push_back(record);
     }
 
-    pub fn handle_join(&mut self, id: u32) {
-        let team = TeamId::TeamA; // simple for now
-        let mut p = Player::new(id, team);
-        p.respawn();
-        self.players.insert(id, p);
-        println!("[NET] Player {} joined", id);
+    /// Feed a measured round trip into the connection's player.
+    pub fn record_rtt(&mut self, conn_id: u32, rtt: Duration) {
+        let Some(eid) = self.connections.get(&conn_id) else { return };
+        if let Some(player) = self.entities.players.get_mut(eid) {
+            player.latency.add_sample(rtt);
+        }
     }
 
-    pub fn handle_disconnect(&mut self, id: u32) {
-        self.players.remove(&id);
-        println!("[NET] Player {} disconnected", id);
+    /// Forget sessions whose grace period ran out.
+    fn expire_sessions(&mut self) {
+        let now = Instant::now();
+        self.sessions.retain(|_, s| {
+            let expired = s.is_expired(now);
+            if expired {
+                info!(target: "net", player = s.eid, "session expired");
+            }
+            !expired
+        });
     }
 
     pub fn update(
@@ -70,64 +308,107 @@ impl SimState {
         input_map: &HashMap<u32, ClientMessage>,
     ) -> TickSnapshot {
         // Debug: how many inputs did we get this tick?
-        println!("[DEBUG] input_map len = {}", input_map.len());
+        trace!(target: "sim", inputs = input_map.len(), "tick");
     
         self.frame_count += 1;
+        self.expire_sessions();
     
         // 1. Run Systems
-        systems::movement::update(&mut self.players, input_map, dt, self.frame_count);
+        systems::movement::update(
+            &mut self.entities,
+            input_map,
+            self.movement_authority,
+            dt,
+            self.frame_count,
+        );
+        systems::vehicles::update(
+            &mut self.entities,
+            &self.vehicle_spawns,
+            &mut self.vehicle_requests,
+            input_map,
+            &self.colliders,
+            &self.bounds,
+            &self.rules,
+            dt,
+        );
+        systems::validation::update(
+            &mut self.entities,
+            &mut self.movement_violations,
+            self.movement_authority,
+            dt,
+            self.frame_count,
+        );
     
-        // Existing [DEBUG] After movement + conquest calls stay as-is
-        if let Some((id, p)) = self.players.iter().next() {
-            println!(
-                "[DEBUG] After movement: Player {} at ({:.1}, {:.1}, {:.1}) team={:?}",
-                id, p.transform.x, p.transform.y, p.transform.z, p.team
-            );
-        }
-        systems::combat::update(&mut self.players, input_map, dt);
+        systems::environment::update(&mut self.entities, &self.bounds, &self.kill_volumes, &self.rules, dt);
+        systems::combat::update(&mut self.entities, input_map, &self.rules, dt);
+        systems::projectiles::update(
+            &mut self.entities,
+            input_map,
+            &self.colliders,
+            &self.bounds,
+            &self.rules,
+            dt,
+        );
 
         // 2. Conquest logic: update flag capture + tickets
         systems::conquest::update_conquest(
             &mut self.flags,
-            &self.players,
+            &self.entities,
             &mut self.tickets_a,
             &mut self.tickets_b,
+            &self.rules,
             dt,
         );
 
+        self.snapshot()
+    }
+
+    /// Current state as sent to clients, without advancing the sim.
+    pub fn snapshot(&self) -> TickSnapshot {
         // 3. Game Mode Logic (winner)
-let winner = if self.tickets_a <= 0.0 {
-    TeamId::TeamB
-} else if self.tickets_b <= 0.0 {
-    TeamId::TeamA
-} else {
-    TeamId::None
-};
+        let winner = if self.tickets_a <= 0.0 {
+            TeamId::TeamB
+        } else if self.tickets_b <= 0.0 {
+            TeamId::TeamA
+        } else {
+            TeamId::None
+        };
 
-        // 4. Snapshot Generation: entities
-        let mut entities = Vec::new();
-        for p in self.players.values() {
-            entities.push(EntitySnapshot {
-                eid: p.id,
-                transform: p.transform.clone(),
-                health: Some(HealthStruct {
-                    current: p.health,
-                    max: p.max_health,
-                }),
-                stamina: Some(StaminaStruct {
-                    current: 100.0,
-                    max: 100.0,
-                }),
-                team: Some(TeamStruct { id: p.team }),
-                score: Some(ScoreStruct {
-                    kills: p.score_kills,
-                    deaths: p.score_deaths,
-                }),
-                loadout: Some(LoadoutStruct {
-                    class_id: p.class_id,
-                }),
-            });
-        }
+        // 4. Snapshot Generation: every entity with a transform
+        let store = &self.entities;
+        let entities = store
+            .kinds
+            .iter()
+            .filter_map(|(id, kind)| {
+                let transform = store.transforms.get(id)?;
+                Some(EntitySnapshot {
+                    eid: *id,
+                    transform: *transform,
+                    health: store.healths.get(id).map(|h| HealthStruct {
+                        current: h.current,
+                        max: h.max,
+                    }),
+                    stamina: store.players.get(id).map(|p| StaminaStruct {
+                        current: p.stamina,
+                        max: p.max_stamina,
+                    }),
+                    team: store.teams.get(id).map(|t| TeamStruct { id: *t }),
+                    score: store.scores.get(id).map(|s| ScoreStruct {
+                        kills: s.kills,
+                        deaths: s.deaths,
+                    }),
+                    loadout: store.loadouts.get(id).map(|l| LoadoutStruct {
+                        class_id: l.class_id,
+                    }),
+                    kind: *kind,
+                    name: store.names.get(id).cloned(),
+                    latency: store.players.get(id).map(|p| LatencyStruct {
+                        rtt_ms: p.latency.rtt_ms,
+                        jitter_ms: p.latency.jitter_ms,
+                    }),
+                })
+            })
+            .collect();
 
         // 5. Snapshot Generation: flags
         let flags = self.flags.iter().map(|f| FlagSnapshot {
@@ -140,6 +421,46 @@ let winner = if self.tickets_a <= 0.0 {
             capture: f.capture,
         }).collect();
 
+        // 6. Snapshot Generation: projectile-only state
+        let mut projectiles: Vec<ProjectileSnapshot> = store.projectiles.values().map(|p| ProjectileSnapshot {
+            id: p.id,
+            kind: p.kind,
+            owner: p.owner,
+            vx: p.velocity.0,
+            vy: p.velocity.1,
+            vz: p.velocity.2,
+        }).collect();
+        // Sorted so consecutive snapshots line up for deltas.
+        projectiles.sort_by_key(|p| p.id);
+
+        // 7. Snapshot Generation: vehicle-only state
+        let mut vehicles: Vec<VehicleSnapshot> = store.vehicles.values().map(|v| VehicleSnapshot {
+            id: v.id,
+            kind: v.kind,
+            seats: v.seats.clone(),
+            destroyed: v.is_destroyed,
+        }).collect();
+        vehicles.sort_by_key(|v| v.id);
+
+        // 8. Scoreboard
+        let mut scoreboard: Vec<ScoreboardEntry> = store
+            .players
+            .keys()
+            .filter_map(|id| {
+                let score = store.scores.get(id)?;
+                Some(ScoreboardEntry {
+                    eid: *id,
+                    name: store.names.get(id).cloned().unwrap_or_default(),
+                    team: store.teams.get(id).copied().unwrap_or(TeamId::None),
+                    kills: score.kills,
+                    deaths: score.deaths,
+                })
+            })
+            .collect();
+        scoreboard.sort_by(|a, b| {
+            b.kills.cmp(&a.kills).then(a.deaths.cmp(&b.deaths)).then(a.eid.cmp(&b.eid))
+        });
+
         TickSnapshot {
             entities,
             flags,
@@ -149,6 +470,144 @@ let winner = if self.tickets_a <= 0.0 {
                 match_ended: winner != TeamId::None,
                 winner,
             },
+            projectiles,
+            vehicles,
+            scoreboard,
+            tick: self.frame_count,
         }        
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn join(sim: &mut SimState) -> (u32, EntityId) {
+        let conn = sim.allocate_connection_id();
+        let eid = sim.handle_join(conn, "tester", None).expect("join failed");
+        (conn, eid)
+    }
+
+    #[test]
+    fn rejoin_after_leave_does_not_reuse_live_ids() {
+        let mut sim = SimState::new();
+        let (c1, e1) = join(&mut sim);
+        let (c2, e2) = join(&mut sim);
+
+        // Player 1 of 2 leaves; the next joiner must not collide with player 2.
+        sim.handle_disconnect(c1, DisconnectReason::ClientClosed);
+        let (c3, e3) = join(&mut sim);
+
+        assert_ne!(c3, c2);
+        assert_ne!(c3, c1);
+        assert_ne!(e3, e2);
+        assert_ne!(e3, e1);
+        assert_eq!(sim.connections.len(), 2);
+        assert_eq!(sim.connections[&c2], e2);
+        assert_eq!(sim.connections[&c3], e3);
+        assert!(sim.entities.players.contains_key(&e2));
+        assert!(!sim.entities.players.contains_key(&e1));
+        assert_eq!(sim.entities.players[&e3].conn_id, c3);
+    }
+
+    #[test]
+    fn full_server_refuses_new_players_but_keeps_their_slots() {
+        let mut config = ServerConfig::default();
+        config.server.max_players = 2;
+        let mut sim = SimState::with_config(&config);
+        let (c1, _) = join(&mut sim);
+        join(&mut sim);
+
+        let conn = sim.allocate_connection_id();
+        assert!(sim.handle_join(conn, "late", None).is_err());
+
+        // A dropped player still holds their slot until the grace period ends.
+        let token = sim.session_token(c1).unwrap().to_string();
+        sim.handle_disconnect(c1, DisconnectReason::Timeout);
+        assert!(sim.handle_join(conn, "late", None).is_err());
+        assert!(sim.resume_session(conn, &token, "tester").is_ok());
+    }
+
+    #[test]
+    fn join_leave_join_sequence() {
+        let mut sim = SimState::new();
+        let mut seen = std::collections::HashSet::new();
+
+        for _ in 0..5 {
+            let (a, _) = join(&mut sim);
+            let (b, _) = join(&mut sim);
+            assert!(seen.insert(a));
+            assert!(seen.insert(b));
+            sim.handle_disconnect(a, DisconnectReason::Timeout);
+        }
+
+        // One connection left over from each round.
+        assert_eq!(sim.connections.len(), 5);
+        assert_eq!(sim.disconnects.len(), 5);
+        assert!(sim.disconnects.iter().all(|d| d.reason == DisconnectReason::Timeout));
+        assert_eq!(sim.entities.players.len(), 5);
+    }
+
+    #[test]
+    fn resume_restores_player_state() {
+        let mut sim = SimState::new();
+        let (conn, eid) = join(&mut sim);
+        let token = sim.session_token(conn).unwrap().to_string();
+        sim.entities.teams.insert(eid, TeamId::TeamB);
+        sim.entities.scores.get_mut(&eid).unwrap().kills = 3;
+        sim.entities.loadouts.get_mut(&eid).unwrap().class_id = 2;
+
+        sim.handle_disconnect(conn, DisconnectReason::Error("reset".into()));
+        assert!(!sim.entities.players.contains_key(&eid));
+
+        let conn2 = sim.allocate_connection_id();
+        assert_eq!(sim.resume_session(conn2, &token, "tester"), Ok(eid));
+        assert_eq!(sim.entities.players[&eid].conn_id, conn2);
+        assert_eq!(sim.entities.teams[&eid], TeamId::TeamB);
+        assert_eq!(sim.entities.scores[&eid].kills, 3);
+        assert_eq!(sim.entities.loadouts[&eid].class_id, 2);
+        assert_eq!(sim.session_token(conn2), Some(token.as_str()));
+
+        // A session can only be claimed by one connection at a time.
+        let conn3 = sim.allocate_connection_id();
+        assert!(sim.resume_session(conn3, &token, "tester").is_err());
+        assert!(sim.resume_session(conn3, "not-a-token", "tester").is_err());
+    }
+
+    #[test]
+    fn duplicate_join_is_rejected() {
+        let mut sim = SimState::new();
+        let (conn, eid) = join(&mut sim);
+
+        assert!(sim.handle_join(conn, "tester", None).is_err());
+        assert_eq!(sim.connections[&conn], eid);
+        assert_eq!(sim.entities.players.len(), 1);
+    }
+
+    #[test]
+    fn names_are_sanitized() {
+        let mut sim = SimState::new();
+        let conn = sim.allocate_connection_id();
+        let eid = sim.handle_join(conn, "  <b>Sgt.\n  Rock</b>  of the 42nd battalion", None).unwrap();
+        assert_eq!(sim.entities.names[&eid], "bSgt. Rockb of t");
+
+        let conn = sim.allocate_connection_id();
+        let eid = sim.handle_join(conn, "\u{7}\t<>", None).unwrap();
+        assert_eq!(sim.entities.names[&eid], format!("Player {}", eid));
+    }
+
+    #[test]
+    fn team_requests_respect_balance() {
+        let mut sim = SimState::new();
+        let mut join_as = |team| {
+            let conn = sim.allocate_connection_id();
+            let eid = sim.handle_join(conn, "tester", team).unwrap();
+            sim.entities.teams[&eid]
+        };
+        assert_eq!(join_as(Some(TeamId::TeamB)), TeamId::TeamB);
+        // B is ahead, so a second B request goes to A.
+        assert_eq!(join_as(Some(TeamId::TeamB)), TeamId::TeamA);
+        assert_eq!(join_as(None), TeamId::TeamA);
+        assert_eq!(join_as(None), TeamId::TeamB);
+    }
+}
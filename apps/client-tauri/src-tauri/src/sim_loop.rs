@@ -0,0 +1,420 @@
+// apps/client-tauri/src-tauri/src/sim_loop.rs
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::sync::oneshot;
+use tracing::info;
+
+use crate::compact;
+use crate::entity::EntityId;
+use crate::events::{MatchPhase, ServerEvent, ServerStats, Traffic};
+use crate::protocol::{ClientMessage, CompactFormat, TeamId, TickSnapshot};
+use crate::session::DisconnectReason;
+use crate::sim::{Collider, SimState};
+use crate::stream::{Frame, FRAME_BACKLOG};
+use crate::vehicle::VehicleRequest;
+
+// The sim owns its own thread, so a slow step never holds up the async
+// runtime. Connections talk to it through `SimHandle`: commands go in over
+// a channel, each tick's snapshot comes out as a broadcast `Frame`. Nothing
+// else touches `SimState`. Dashboard events (joins, captures, stats) come
+// out of the same thread as `ServerEvent`s.
+
+// Events kept for a dashboard that falls behind.
+const EVENT_BACKLOG: usize = 64;
+// How often `ServerEvent::Stats` goes out.
+const STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A connection asking to join, or resume its session.
+pub struct JoinRequest {
+    pub name: String,
+    pub requested_team: Option<TeamId>,
+    pub resume_token: Option<String>,
+    // Client and server both allow the compact encoding.
+    pub compact: bool,
+}
+
+/// What a connection needs from the sim to start streaming.
+pub struct Joined {
+    pub conn_id: u32,
+    pub eid: EntityId,
+    // Reclaimed an earlier session rather than joining fresh.
+    pub resumed: bool,
+    pub session_token: Option<String>,
+    // State at join time; `frames` continues from the next tick.
+    pub snapshot: TickSnapshot,
+    pub frames: broadcast::Receiver<Arc<Frame>>,
+    pub compact: Option<CompactFormat>,
+    pub colliders: Vec<Collider>,
+}
+
+pub enum SimCommand {
+    Join(JoinRequest, oneshot::Sender<Result<Joined, String>>),
+    // Latest input, state update or fire; replaces the previous one.
+    Input(u32, ClientMessage),
+    Vehicle(u32, VehicleRequest),
+    Rtt(u32, Duration),
+    Disconnect(u32, DisconnectReason),
+    // Players counted against max_players (connected or still inside their
+    // reconnect grace period), e.g. for LAN discovery replies.
+    PlayerCount(oneshot::Sender<usize>),
+}
+
+/// Cheap to clone; the sim thread stops once every handle is dropped.
+#[derive(Clone)]
+pub struct SimHandle {
+    commands: UnboundedSender<SimCommand>,
+    events: broadcast::Sender<ServerEvent>,
+    // Connections count their bytes here for the stats.
+    traffic: Arc<Traffic>,
+}
+
+impl SimHandle {
+    /// Queue a command for the next tick. Fails silently once the sim has stopped.
+    pub fn send(&self, command: SimCommand) {
+        let _ = self.commands.send(command);
+    }
+
+    pub async fn join(&self, request: JoinRequest) -> Result<Joined, String> {
+        let (reply, joined) = oneshot::channel();
+        self.commands
+            .send(SimCommand::Join(request, reply))
+            .map_err(|_| "server is shutting down".to_string())?;
+        joined.await.map_err(|_| "server is shutting down".to_string())?
+    }
+
+    pub fn events(&self) -> broadcast::Receiver<ServerEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn traffic(&self) -> &Arc<Traffic> {
+        &self.traffic
+    }
+
+    /// None once the sim has stopped.
+    pub async fn player_count(&self) -> Option<usize> {
+        let (reply, count) = oneshot::channel();
+        self.commands.send(SimCommand::PlayerCount(reply)).ok()?;
+        count.await.ok()
+    }
+}
+
+/// Start the sim thread, stepping every `tick`.
+pub fn spawn(state: SimState, tick: Duration) -> SimHandle {
+    let (commands, rx) = unbounded_channel();
+    let (frames, _) = broadcast::channel(FRAME_BACKLOG);
+    let (events, _) = broadcast::channel(EVENT_BACKLOG);
+    let traffic = Arc::new(Traffic::default());
+    let reporter = Reporter::new(&state, events.clone(), Arc::clone(&traffic));
+    thread::Builder::new()
+        .name("sim".to_string())
+        .spawn(move || run(state, rx, frames, reporter, tick))
+        .expect("failed to start sim thread");
+    SimHandle { commands, events, traffic }
+}
+
+fn run(
+    mut sim: SimState,
+    mut commands: UnboundedReceiver<SimCommand>,
+    frames: broadcast::Sender<Arc<Frame>>,
+    mut reporter: Reporter,
+    tick: Duration,
+) {
+    let mut inputs: HashMap<u32, ClientMessage> = HashMap::new();
+    let mut last = Instant::now();
+    let mut next = last + tick;
+
+    loop {
+        loop {
+            match commands.try_recv() {
+                Ok(command) => apply(&mut sim, &mut inputs, &frames, &reporter, command),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    info!(target: "sim", "all handles dropped, stopping");
+                    return;
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let dt = now.duration_since(last).as_secs_f32();
+        last = now;
+        // This runs movement, combat and conquest, and builds a TickSnapshot.
+        let snapshot = sim.update(dt, &inputs);
+        reporter.after_tick(&sim, &snapshot, now.elapsed());
+        // No receivers just means nobody is connected.
+        let _ = frames.send(Arc::new(Frame::new(snapshot)));
+
+        // Fixed rate; after a long step, start counting again from now.
+        let now = Instant::now();
+        if next > now {
+            thread::sleep(next - now);
+            next += tick;
+        } else {
+            next = now + tick;
+        }
+    }
+}
+
+fn apply(
+    sim: &mut SimState,
+    inputs: &mut HashMap<u32, ClientMessage>,
+    frames: &broadcast::Sender<Arc<Frame>>,
+    reporter: &Reporter,
+    command: SimCommand,
+) {
+    match command {
+        SimCommand::Join(request, reply) => {
+            let joined = join(sim, frames, request);
+            if let Ok(joined) = &joined {
+                reporter.send(ServerEvent::PlayerJoined {
+                    player: joined.eid,
+                    name: sim.entities.names.get(&joined.eid).cloned().unwrap_or_default(),
+                    team: sim.entities.teams.get(&joined.eid).copied().unwrap_or(TeamId::None),
+                    resumed: joined.resumed,
+                });
+            }
+            let _ = reply.send(joined);
+        }
+        SimCommand::Input(conn_id, msg) => {
+            inputs.insert(conn_id, msg);
+        }
+        SimCommand::Vehicle(conn_id, request) => sim.queue_vehicle_request(conn_id, request),
+        SimCommand::Rtt(conn_id, rtt) => sim.record_rtt(conn_id, rtt),
+        SimCommand::Disconnect(conn_id, reason) => {
+            inputs.remove(&conn_id);
+            let Some(&eid) = sim.connections.get(&conn_id) else { return };
+            let name = sim.entities.names.get(&eid).cloned().unwrap_or_default();
+            let left = ServerEvent::PlayerLeft { player: eid, name, reason: reason.to_string() };
+            sim.handle_disconnect(conn_id, reason);
+            reporter.send(left);
+        }
+        SimCommand::PlayerCount(reply) => {
+            let _ = reply.send(sim.sessions.len());
+        }
+    }
+}
+
+fn join(
+    sim: &mut SimState,
+    frames: &broadcast::Sender<Arc<Frame>>,
+    request: JoinRequest,
+) -> Result<Joined, String> {
+    let conn_id = sim.allocate_connection_id();
+    let resumed = request.resume_token.as_deref().and_then(|token| {
+        sim.resume_session(conn_id, token, &request.name)
+            .map_err(|e| info!(target: "net", name = %request.name, error = %e, "resume failed, joining as new player"))
+            .ok()
+    });
+    let eid = match resumed {
+        Some(eid) => eid,
+        None => sim.handle_join(conn_id, &request.name, request.requested_team)?,
+    };
+    Ok(Joined {
+        conn_id,
+        eid,
+        resumed: resumed.is_some(),
+        session_token: sim.session_token(conn_id).map(str::to_string),
+        snapshot: sim.snapshot(),
+        // Subscribed between ticks, so no frame is missed or repeated.
+        frames: frames.subscribe(),
+        compact: request.compact.then(|| compact::format_for(&sim.bounds)).flatten(),
+        colliders: sim.colliders.clone(),
+    })
+}
+
+/// Turns what happens on the sim thread into `ServerEvent`s.
+struct Reporter {
+    events: broadcast::Sender<ServerEvent>,
+    traffic: Arc<Traffic>,
+    // None until the first tick, so the starting phase is reported too.
+    phase: Option<MatchPhase>,
+    flag_owners: Vec<(u32, TeamId)>,
+    // Current stats window.
+    window_start: Instant,
+    ticks: u32,
+    busy: Duration,
+    longest: Duration,
+    last_totals: (u64, u64),
+}
+
+impl Reporter {
+    fn new(sim: &SimState, events: broadcast::Sender<ServerEvent>, traffic: Arc<Traffic>) -> Self {
+        Self {
+            events,
+            traffic,
+            phase: None,
+            flag_owners: sim.flags.iter().map(|f| (f.id, f.owner)).collect(),
+            window_start: Instant::now(),
+            ticks: 0,
+            busy: Duration::ZERO,
+            longest: Duration::ZERO,
+            last_totals: (0, 0),
+        }
+    }
+
+    fn send(&self, event: ServerEvent) {
+        // No receivers just means nobody is watching.
+        let _ = self.events.send(event);
+    }
+
+    fn after_tick(&mut self, sim: &SimState, snapshot: &TickSnapshot, took: Duration) {
+        for flag in &sim.flags {
+            let Some(known) = self.flag_owners.iter_mut().find(|(id, _)| *id == flag.id) else { continue };
+            if known.1 != flag.owner {
+                let previous = std::mem::replace(&mut known.1, flag.owner);
+                let _ = self.events.send(ServerEvent::FlagCaptured { flag: flag.id, owner: flag.owner, previous });
+            }
+        }
+
+        let game = &snapshot.game_state;
+        let phase = MatchPhase::of(sim.connections.len(), game);
+        if self.phase != Some(phase) {
+            self.phase = Some(phase);
+            self.send(ServerEvent::MatchPhase {
+                phase,
+                winner: game.winner,
+                team_a_tickets: game.team_a_tickets,
+                team_b_tickets: game.team_b_tickets,
+            });
+        }
+
+        self.ticks += 1;
+        self.busy += took;
+        self.longest = self.longest.max(took);
+        let window = self.window_start.elapsed();
+        if window < STATS_INTERVAL {
+            return;
+        }
+        let (out, inbound) = self.traffic.totals();
+        let per_sec = |bytes: u64| (bytes as f64 / window.as_secs_f64()) as u64;
+        self.send(ServerEvent::Stats(ServerStats {
+            ticks: self.ticks,
+            tick_avg_ms: self.busy.as_secs_f32() * 1000.0 / self.ticks as f32,
+            tick_max_ms: self.longest.as_secs_f32() * 1000.0,
+            players: sim.connections.len() as u32,
+            bytes_out_per_sec: per_sec(out - self.last_totals.0),
+            bytes_in_per_sec: per_sec(inbound - self.last_totals.1),
+        }));
+        self.last_totals = (out, inbound);
+        self.window_start = Instant::now();
+        self.ticks = 0;
+        self.busy = Duration::ZERO;
+        self.longest = Duration::ZERO;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn joined_clients_get_every_following_tick() {
+        // Slow enough that the frame backlog covers a stalled test thread.
+        let sim = spawn(SimState::new(), Duration::from_millis(50));
+        let mut joined = sim
+            .join(JoinRequest {
+                name: "alice".to_string(),
+                requested_team: None,
+                resume_token: None,
+                compact: true,
+            })
+            .await
+            .unwrap();
+        assert!(joined.compact.is_some());
+        assert!(joined.snapshot.entities.iter().any(|e| e.eid == joined.eid));
+
+        let mut tick = joined.snapshot.tick;
+        for _ in 0..5 {
+            let frame = joined.frames.recv().await.unwrap();
+            assert_eq!(frame.snapshot.tick, tick + 1);
+            tick = frame.snapshot.tick;
+        }
+
+        sim.send(SimCommand::Disconnect(joined.conn_id, DisconnectReason::ClientClosed));
+        let mut gone = false;
+        for _ in 0..20 {
+            let frame = match joined.frames.recv().await {
+                Ok(frame) => frame,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(e) => panic!("frames closed: {}", e),
+            };
+            if frame.snapshot.entities.iter().all(|e| e.eid != joined.eid) {
+                gone = true;
+                break;
+            }
+        }
+        assert!(gone);
+    }
+
+    #[tokio::test]
+    async fn dashboard_hears_about_players_and_stats() {
+        let sim = spawn(SimState::new(), Duration::from_millis(5));
+        let mut events = sim.events();
+        let joined = sim
+            .join(JoinRequest {
+                name: "alice".to_string(),
+                requested_team: None,
+                resume_token: None,
+                compact: false,
+            })
+            .await
+            .unwrap();
+        sim.send(SimCommand::Disconnect(joined.conn_id, DisconnectReason::ClientClosed));
+
+        let mut players = Vec::new();
+        let stats = tokio::time::timeout(Duration::from_secs(3), async {
+            loop {
+                match events.recv().await.unwrap() {
+                    ServerEvent::Stats(stats) => return stats,
+                    event @ (ServerEvent::PlayerJoined { .. } | ServerEvent::PlayerLeft { .. }) => players.push(event),
+                    _ => {}
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert!(matches!(&players[0], ServerEvent::PlayerJoined { name, resumed: false, .. } if name == "alice"));
+        assert!(matches!(&players[1], ServerEvent::PlayerLeft { reason, .. } if reason == "client closed"));
+        assert!(stats.ticks > 0);
+        assert!(stats.tick_max_ms >= stats.tick_avg_ms);
+    }
+
+    #[test]
+    fn phase_and_capture_changes_are_reported_once() {
+        let mut sim = SimState::new();
+        let (tx, mut rx) = broadcast::channel(EVENT_BACKLOG);
+        let mut reporter = Reporter::new(&sim, tx, Arc::default());
+        let mut tick = |sim: &mut SimState| {
+            let snapshot = sim.update(0.05, &HashMap::new());
+            reporter.after_tick(sim, &snapshot, Duration::ZERO);
+        };
+
+        tick(&mut sim);
+        assert!(matches!(rx.try_recv(), Ok(ServerEvent::MatchPhase { phase: MatchPhase::Waiting, .. })));
+        tick(&mut sim);
+        assert!(rx.try_recv().is_err());
+
+        let flag = sim.flags[0].id;
+        sim.flags[0].owner = TeamId::TeamB;
+        sim.flags[0].capture = -1.0;
+        sim.tickets_a = 0.0;
+        tick(&mut sim);
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(ServerEvent::FlagCaptured { flag: f, owner: TeamId::TeamB, previous: TeamId::None }) if f == flag
+        ));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(ServerEvent::MatchPhase { phase: MatchPhase::Ended, winner: TeamId::TeamB, .. })
+        ));
+        tick(&mut sim);
+        assert!(rx.try_recv().is_err());
+    }
+}
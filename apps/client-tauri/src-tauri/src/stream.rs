@@ -0,0 +1,369 @@
+// apps/client-tauri/src-tauri/src/stream.rs
+use std::collections::{HashMap, VecDeque};
+use std::sync::OnceLock;
+
+use rmp_serde::to_vec as rmp_to_vec;
+
+use crate::compact;
+use crate::delta;
+use crate::entity::EntityId;
+use crate::protocol::{CompactFormat, EntitySnapshot, ServerEnvelope, TickSnapshot};
+use crate::relevance::{Interest, RelevanceConfig};
+use crate::sim::Collider;
+
+// Frames the broadcast channel holds for a slow client before it skips ahead.
+pub const FRAME_BACKLOG: usize = 8;
+// Sent snapshots kept per client as delta baselines. A client whose last ack
+// is older than this gets a full snapshot.
+const BASELINE_HISTORY: usize = 32;
+
+/// One tick's snapshot, built once by the tick loop and shared with every
+/// client's sender task through a broadcast channel.
+///
+/// How much of the encoding is shared depends on what the client gets:
+/// - whole snapshots (relevance off, no baseline): encoded once per tick;
+/// - filtered msgpack snapshots (relevance on, no baseline, which is what
+///   the bundled client gets): each entity is encoded once per tick and
+///   copied into every client's subset;
+/// - deltas and filtered compact snapshots: per client, since both depend
+///   on what that client was sent before.
+pub struct Frame {
+    pub snapshot: TickSnapshot,
+    // Encoded snapshot for clients that get it whole; only their `your_id`
+    // differs. The server uses one map, so there is only ever one compact
+    // format.
+    msgpack: OnceLock<Vec<u8>>,
+    compact: OnceLock<Vec<u8>>,
+    // Per-entity msgpack, with the entity's index in `snapshot.entities`.
+    entities: OnceLock<HashMap<EntityId, (usize, Vec<u8>)>>,
+}
+
+impl Frame {
+    pub fn new(snapshot: TickSnapshot) -> Self {
+        Self {
+            snapshot,
+            msgpack: OnceLock::new(),
+            compact: OnceLock::new(),
+            entities: OnceLock::new(),
+        }
+    }
+
+    /// Envelope with the whole snapshot, reusing the shared encoding.
+    fn full_envelope(&self, your_id: EntityId, format: Option<&CompactFormat>) -> Vec<u8> {
+        match format {
+            Some(format) => {
+                let body = self
+                    .compact
+                    .get_or_init(|| compact::encode_snapshot(format, &self.snapshot));
+                compact::snapshot_envelope(format, your_id, body)
+            }
+            None => {
+                let body = self
+                    .msgpack
+                    .get_or_init(|| rmp_to_vec(&self.snapshot).unwrap());
+                msgpack_snapshot_envelope(your_id, body)
+            }
+        }
+    }
+
+    /// Msgpack envelope with a filtered snapshot. Entities sent as they are
+    /// this tick reuse the shared encoding; stale ones (held back by the
+    /// scheduler) and the filtered lists are encoded for this client.
+    fn filtered_envelope(&self, your_id: EntityId, filtered: &TickSnapshot) -> Vec<u8> {
+        let shared = self.entities.get_or_init(|| {
+            self.snapshot
+                .entities
+                .iter()
+                .enumerate()
+                .map(|(i, e)| (e.eid, (i, rmp_to_vec(e).unwrap())))
+                .collect()
+        });
+        let fresh = |e: &EntitySnapshot| match shared.get(&e.eid) {
+            Some((i, bytes)) if self.snapshot.entities[*i] == *e => Some(bytes),
+            _ => None,
+        };
+
+        // `TickSnapshot` encodes as a 7-element array, entities first.
+        let mut body = Vec::new();
+        body.push(0x97);
+        write_array_len(&mut body, filtered.entities.len());
+        for e in &filtered.entities {
+            match fresh(e) {
+                Some(bytes) => body.extend_from_slice(bytes),
+                None => rmp_serde::encode::write(&mut body, e).unwrap(),
+            }
+        }
+        rmp_serde::encode::write(&mut body, &filtered.flags).unwrap();
+        rmp_serde::encode::write(&mut body, &filtered.game_state).unwrap();
+        rmp_serde::encode::write(&mut body, &filtered.projectiles).unwrap();
+        rmp_serde::encode::write(&mut body, &filtered.vehicles).unwrap();
+        rmp_serde::encode::write(&mut body, &filtered.scoreboard).unwrap();
+        rmp_serde::encode::write(&mut body, &filtered.tick).unwrap();
+        msgpack_snapshot_envelope(your_id, &body)
+    }
+}
+
+fn write_array_len(buf: &mut Vec<u8>, len: usize) {
+    match len {
+        0..=15 => buf.push(0x90 | len as u8),
+        16..=0xffff => {
+            buf.push(0xdc);
+            buf.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        _ => {
+            buf.push(0xdd);
+            buf.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+}
+
+// `ServerEnvelope` encodes as a 7-element msgpack array; with only `your_id`
+// and `snapshot` set, the other five are nil.
+fn msgpack_snapshot_envelope(your_id: EntityId, body: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(body.len() + 16);
+    buf.push(0x97);
+    rmp_serde::encode::write(&mut buf, &your_id).unwrap();
+    buf.extend_from_slice(body);
+    buf.extend_from_slice(&[0xc0; 5]);
+    buf
+}
+
+/// What one client has been sent: relevance state, delta baselines and the
+/// negotiated encoding.
+pub struct SnapshotStream {
+    your_id: EntityId,
+    format: Option<CompactFormat>,
+    relevance: Option<RelevanceConfig>,
+    colliders: Vec<Collider>,
+    interest: Interest,
+    history: VecDeque<TickSnapshot>,
+}
+
+impl SnapshotStream {
+    pub fn new(
+        your_id: EntityId,
+        format: Option<CompactFormat>,
+        relevance: Option<RelevanceConfig>,
+        colliders: Vec<Collider>,
+    ) -> Self {
+        Self {
+            your_id,
+            format,
+            relevance,
+            colliders,
+            interest: Interest::new(),
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Tick of the newest snapshot sent.
+    pub fn last_tick(&self) -> u64 {
+        self.history.back().map_or(0, |s| s.tick)
+    }
+
+    /// The join snapshot, which goes out in the handshake envelope. It is
+    /// filtered like any other so joining doesn't reveal the whole map.
+    pub fn first(&mut self, snapshot: &TickSnapshot) -> TickSnapshot {
+        let first = self.filter(snapshot).unwrap_or_else(|| snapshot.clone());
+        self.history.push_back(first.clone());
+        first
+    }
+
+    /// Encoded envelope for `frame`: a delta if the client's `ack` is still
+    /// in the history, otherwise the full (filtered) snapshot.
+    pub fn encode(&mut self, frame: &Frame, ack: u64) -> Vec<u8> {
+        let filtered = self.filter(&frame.snapshot);
+        let current = filtered.as_ref().unwrap_or(&frame.snapshot);
+
+        // An ack of 0 asks for a full snapshot, even if tick 0 was sent.
+        self.history.retain(|s| s.tick >= ack);
+        let delta = self
+            .history
+            .front()
+            .filter(|base| ack > 0 && base.tick == ack)
+            .map(|base| delta::diff(base, current));
+
+        let bytes = if delta.is_none() && filtered.is_none() {
+            frame.full_envelope(self.your_id, self.format.as_ref())
+        } else if let (None, Some(filtered), None) = (&delta, &filtered, &self.format) {
+            frame.filtered_envelope(self.your_id, filtered)
+        } else {
+            let envelope = ServerEnvelope {
+                your_id: self.your_id,
+                snapshot: delta.is_none().then(|| current.clone()),
+                session_token: None,
+                udp_port: None,
+                udp_token: None,
+                delta,
+                compact: None,
+            };
+            match &self.format {
+                Some(format) => compact::encode_envelope(format, &envelope),
+                None => rmp_to_vec(&envelope).unwrap(),
+            }
+        };
+
+        self.history
+            .push_back(filtered.unwrap_or_else(|| frame.snapshot.clone()));
+        if self.history.len() > BASELINE_HISTORY {
+            self.history.pop_front();
+        }
+        bytes
+    }
+
+    fn filter(&mut self, snapshot: &TickSnapshot) -> Option<TickSnapshot> {
+        let relevance = self.relevance?;
+        Some(self.interest.filter(snapshot, self.your_id, &self.colliders, &relevance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::SimState;
+    use std::collections::HashMap;
+    use std::hint::black_box;
+    use std::time::{Duration, Instant};
+
+    fn envelope(your_id: EntityId, snapshot: TickSnapshot) -> ServerEnvelope {
+        ServerEnvelope {
+            your_id,
+            snapshot: Some(snapshot),
+            session_token: None,
+            udp_port: None,
+            udp_token: None,
+            delta: None,
+            compact: None,
+        }
+    }
+
+    fn sim_with_players(count: usize) -> (SimState, Vec<EntityId>) {
+        let mut sim = SimState::new();
+        let eids = (0..count)
+            .map(|i| {
+                let conn = sim.allocate_connection_id();
+                let eid = sim.handle_join(conn, &format!("p{}", i), None).unwrap();
+                // Spread out so relevance has something to do.
+                let t = sim.entities.transforms.get_mut(&eid).unwrap();
+                t.x = (i % 4) as f32 * 30.0 - 45.0;
+                t.z = (i / 4) as f32 * 30.0 - 30.0;
+                let pos = (t.x, t.y, t.z);
+                sim.entities.players.get_mut(&eid).unwrap().last_valid_pos = pos;
+                eid
+            })
+            .collect();
+        (sim, eids)
+    }
+
+    #[test]
+    fn shared_envelopes_match_per_client_encoding() {
+        let (mut sim, eids) = sim_with_players(3);
+        let frame = Frame::new(sim.update(0.05, &HashMap::new()));
+        let format = compact::format_for(&sim.bounds).unwrap();
+
+        for eid in eids {
+            let full = envelope(eid, frame.snapshot.clone());
+            assert_eq!(frame.full_envelope(eid, None), rmp_to_vec(&full).unwrap());
+            assert_eq!(
+                frame.full_envelope(eid, Some(&format)),
+                compact::encode_envelope(&format, &full)
+            );
+        }
+    }
+
+    #[test]
+    fn filtered_envelopes_match_per_client_encoding() {
+        let (mut sim, eids) = sim_with_players(20);
+        let config = RelevanceConfig::default();
+        let mut stream = SnapshotStream::new(eids[0], None, Some(config), Vec::new());
+        // Same filter state as the stream's, to check its output against.
+        let mut interest = crate::relevance::Interest::new();
+        stream.first(&sim.snapshot());
+        interest.filter(&sim.snapshot(), eids[0], &[], &config);
+        // Players are still falling onto the floor, so far ones that the
+        // scheduler holds back go out stale.
+        let mut stale = false;
+        for _ in 0..10 {
+            let frame = Frame::new(sim.update(0.05, &HashMap::new()));
+            let filtered = interest.filter(&frame.snapshot, eids[0], &[], &config);
+            stale |= filtered.entities.iter().any(|e| !frame.snapshot.entities.contains(e));
+
+            let bytes = stream.encode(&frame, 0);
+            assert_eq!(bytes, rmp_to_vec(&envelope(eids[0], filtered)).unwrap());
+        }
+        assert!(stale);
+    }
+
+    #[test]
+    fn acked_clients_get_deltas() {
+        let (mut sim, eids) = sim_with_players(2);
+        let mut stream = SnapshotStream::new(eids[0], None, None, Vec::new());
+        sim.update(0.05, &HashMap::new());
+        let first = stream.first(&sim.snapshot());
+
+        let frame = Frame::new(sim.update(0.05, &HashMap::new()));
+        let env: ServerEnvelope = rmp_serde::from_slice(&stream.encode(&frame, 0)).unwrap();
+        assert!(env.snapshot.is_some() && env.delta.is_none());
+
+        let frame = Frame::new(sim.update(0.05, &HashMap::new()));
+        let env: ServerEnvelope = rmp_serde::from_slice(&stream.encode(&frame, first.tick)).unwrap();
+        let applied = delta::apply(&first, &env.delta.unwrap()).unwrap();
+        assert_eq!(applied, frame.snapshot);
+    }
+
+    // cargo test --release tick_cost -- --ignored --nocapture
+    #[test]
+    #[ignore = "benchmark"]
+    fn tick_cost_with_12_clients() {
+        const CLIENTS: usize = 12;
+        const TICKS: u32 = 200;
+        let inputs = HashMap::new();
+        let per_tick = |start: Instant| start.elapsed() / TICKS;
+
+        // Before: every client's task advanced the sim and encoded its own snapshot.
+        let (mut sim, eids) = sim_with_players(CLIENTS);
+        let start = Instant::now();
+        for _ in 0..TICKS {
+            for eid in &eids {
+                let snapshot = sim.update(0.05, &inputs);
+                black_box(rmp_to_vec(&envelope(*eid, snapshot)).unwrap());
+            }
+        }
+        let per_client = per_tick(start);
+
+        // Now: one update and one frame per tick, then per-client work only.
+        let run = |relevance: Option<RelevanceConfig>, compact: bool, ack: bool| -> Duration {
+            let (mut sim, eids) = sim_with_players(CLIENTS);
+            let format = compact.then(|| compact::format_for(&sim.bounds)).flatten();
+            let mut streams: Vec<SnapshotStream> = eids
+                .iter()
+                .map(|eid| SnapshotStream::new(*eid, format, relevance, sim.colliders.clone()))
+                .collect();
+            let start = Instant::now();
+            for _ in 0..TICKS {
+                let frame = Frame::new(sim.update(0.05, &inputs));
+                for stream in streams.iter_mut() {
+                    let ack = if ack { stream.last_tick() } else { 0 };
+                    black_box(stream.encode(&frame, ack));
+                }
+            }
+            per_tick(start)
+        };
+        let relevance = crate::network::NetConfig::default().relevance;
+        let shared = run(None, false, false);
+        let shared_compact = run(None, true, false);
+        // The default config, with the bundled client: no acks, no compact.
+        let default = run(relevance, false, false);
+        let filtered_deltas = run(relevance, true, true);
+
+        println!("per-tick cost with {} clients:", CLIENTS);
+        println!("  update + encode per client:       {:?}", per_client);
+        println!("  shared frame, full msgpack:       {:?}", shared);
+        println!("  shared frame, full compact:       {:?}", shared_compact);
+        println!("  default (relevance, msgpack):     {:?}", default);
+        println!("  relevance + deltas + compact:     {:?}", filtered_deltas);
+        assert!(shared < per_client);
+        assert!(default < per_client);
+        assert!(filtered_deltas < per_client);
+    }
+}
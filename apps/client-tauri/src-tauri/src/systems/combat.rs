@@ -1,68 +1,86 @@
 // apps/client-tauri/src-tauri/src/systems/combat.rs
 use std::collections::HashMap;
-use crate::player::{Player, RESPAWN_TIME};
-use crate::protocol::ClientMessage;
+use tracing::{debug, info};
+use crate::config::Rules;
+use crate::entity::{EntityId, EntityStore};
+use crate::protocol::{ClientMessage, ProjectileKind, Transform};
+use crate::vehicle::hull_center;
 
-const DAMAGE_PER_HIT: f32 = 34.0; 
-const FIRE_RATE: f32 = 0.15;
+// How long a damaging hit still earns the kill if the victim then dies to the environment.
+pub const ATTACKER_CREDIT_WINDOW: f32 = 5.0;
 
-pub fn update(players: &mut HashMap<u32, Player>, input_map: &HashMap<u32, ClientMessage>, dt: f32) {
+pub fn update(
+    entities: &mut EntityStore,
+    input_map: &HashMap<u32, ClientMessage>,
+    rules: &Rules,
+    dt: f32,
+) {
     // 1. Cooldowns
-    for (_, player) in players.iter_mut() {
+    let mut respawns: Vec<EntityId> = Vec::new();
+    for (id, player) in entities.players.iter_mut() {
         if player.fire_cooldown > 0.0 { player.fire_cooldown -= dt; }
         if player.is_dead {
             player.respawn_timer -= dt;
-            if player.respawn_timer <= 0.0 { player.respawn(); }
+            if player.respawn_timer <= 0.0 { respawns.push(*id); }
         }
     }
+    for id in respawns {
+        entities.respawn_player(id);
+    }
 
     // 2. Hitscan
-    let mut hits: Vec<(u32, u32)> = Vec::new(); 
+    let mut hits: Vec<(EntityId, EntityId)> = Vec::new(); 
+    let mut vehicle_hits: Vec<(EntityId, EntityId)> = Vec::new();
     
-    let shooter_data: Vec<(u32, f32, f32, f32, f32, f32, bool)> = players.iter()
-        .map(|(id, p)| (*id, p.transform.x, p.transform.y, p.transform.z, p.transform.yaw, p.transform.pitch, p.is_dead))
+    // Seated players use vehicle weapons instead (systems::vehicles).
+    let shooter_data: Vec<(EntityId, u32, Transform, bool)> = entities.players.iter()
+        .filter(|(_, p)| p.vehicle_id.is_none())
+        .filter_map(|(id, p)| Some((*id, p.conn_id, *entities.transforms.get(id)?, p.is_dead)))
         .collect();
 
-        for (shooter_id, sx, sy, sz, syaw, spitch, sdead) in &shooter_data {
+        for (shooter_id, conn_id, st, sdead) in &shooter_data {
             if *sdead {
                 continue;
             }
     
-            if let Some(msg) = input_map.get(shooter_id) {
+            if let Some(msg) = input_map.get(conn_id) {
                 // Derive the "fire" flag from the new client message shape.
                 let fire = match msg {
                     ClientMessage::Input { axes, .. } => axes.shoot,
-                    ClientMessage::Fire { .. } => true,           // explicit fire message = fire
+                    // Projectile weapons (grenades) are handled by the projectile system.
+                    ClientMessage::Fire { weapon_id, .. } => ProjectileKind::from_weapon_id(*weapon_id).is_none(),
                     _ => false,
                 };
     
-                let current_cooldown = players
+                let current_cooldown = entities.players
                     .get(shooter_id)
                     .map(|p| p.fire_cooldown)
                     .unwrap_or(0.0);
     
                 if fire && current_cooldown <= 0.0 {
                     hits.push((*shooter_id, 0));
-                    println!("[COMBAT] Player {} FIRED!", shooter_id);
+                    debug!(target: "combat", player = *shooter_id, "fired");
     
-                    let origin_x = *sx;
-                    let origin_y = *sy + 0.6;
-                    let origin_z = *sz;
+                    let origin_x = st.x;
+                    let origin_y = st.y + 0.6;
+                    let origin_z = st.z;
     
                     // Direction: already matched to client camera
-                    let dir_x = syaw.sin() * spitch.cos();
-                    let dir_y = spitch.sin();
-                    let dir_z = syaw.cos() * spitch.cos();
+                    let dir_x = st.yaw.sin() * st.pitch.cos();
+                    let dir_y = st.pitch.sin();
+                    let dir_z = st.yaw.cos() * st.pitch.cos();
     
-                    let mut best_dist = 1000.0;
+                    let mut best_dist = rules.hitscan_range;
                     let mut hit_victim = None;    
 
-                for (victim_id, victim) in players.iter() {
-                    if shooter_id == victim_id || victim.is_dead { continue; }
+                for (victim_id, victim) in entities.players.iter() {
+                    // Vehicle occupants are shielded; shoot the vehicle instead.
+                    if shooter_id == victim_id || victim.is_dead || victim.vehicle_id.is_some() { continue; }
+                    let Some(t) = entities.transforms.get(victim_id) else { continue };
                     
-                    let vx = victim.transform.x;
-                    let vy = victim.transform.y + 0.9; 
-                    let vz = victim.transform.z;
+                    let vx = t.x;
+                    let vy = t.y + 0.9; 
+                    let vz = t.z;
                     let radius = 1.0; 
 
                     let oc_x = vx - origin_x;
@@ -80,42 +98,92 @@ pub fn update(players: &mut HashMap<u32, Player>, input_map: &HashMap<u32, Clien
                     }
                 }
 
+                let mut hit_vehicle = None;
+                for vehicle in entities.vehicles.values() {
+                    if vehicle.is_destroyed { continue; }
+                    let Some(t) = entities.transforms.get(&vehicle.id) else { continue };
+                    let (cx, cy, cz) = hull_center(t);
+                    if let Some(d) = ray_sphere_distance(
+                        (origin_x, origin_y, origin_z),
+                        (dir_x, dir_y, dir_z),
+                        (cx, cy, cz),
+                        vehicle.kind.hit_radius(),
+                    ) {
+                        if d < best_dist {
+                            best_dist = d;
+                            hit_victim = None;
+                            hit_vehicle = Some(vehicle.id);
+                        }
+                    }
+                }
+
                 if let Some(vid) = hit_victim {
                     hits.pop(); 
                     hits.push((*shooter_id, vid));
-                    println!("[COMBAT] -> HIT Player {}!", vid);
+                    debug!(target: "combat", shooter = *shooter_id, victim = vid, "hit player");
+                } else if let Some(vehicle_id) = hit_vehicle {
+                    vehicle_hits.push((*shooter_id, vehicle_id));
+                    debug!(target: "combat", shooter = *shooter_id, vehicle = vehicle_id, "hit vehicle");
                 } else {
-                    println!("[COMBAT] -> MISSED");
+                    debug!(target: "combat", shooter = *shooter_id, "missed");
                 }
             }
         }
     }
 
     // 3. Apply Damage
-    let mut kills_to_award: Vec<u32> = Vec::new();
+    let mut kills_to_award: Vec<EntityId> = Vec::new();
     for (shooter_id, victim_id) in hits {
-        if let Some(p) = players.get_mut(&shooter_id) { p.fire_cooldown = FIRE_RATE; }
+        if let Some(p) = entities.players.get_mut(&shooter_id) { p.fire_cooldown = rules.fire_interval; }
 
-        if victim_id != 0 {
-            let mut killed = false;
-            if let Some(victim) = players.get_mut(&victim_id) {
-                victim.health -= DAMAGE_PER_HIT;
-                println!("[COMBAT] Player {} HP: {:.1}", victim_id, victim.health);
-                if victim.health <= 0.0 {
-                    victim.health = 0.0;
-                    victim.is_dead = true;
-                    victim.respawn_timer = RESPAWN_TIME;
-                    victim.score_deaths += 1;
-                    println!("[COMBAT] Player {} ELIMINATED by Player {}", victim_id, shooter_id);
-                    killed = true;
-                }
+        // Without friendly fire a teammate still stops the bullet, unharmed.
+        if victim_id != 0 && (rules.friendly_fire || !entities.teammates(shooter_id, victim_id)) {
+            let Some(victim) = entities.players.get_mut(&victim_id) else { continue };
+            if victim.is_dead { continue; }
+            victim.last_attacker = Some(shooter_id);
+            victim.last_attacker_timer = ATTACKER_CREDIT_WINDOW;
+
+            let Some(hp) = entities.damage(victim_id, rules.damage_per_hit) else { continue };
+            debug!(target: "combat", player = victim_id, hp, "damaged");
+            if hp <= 0.0 {
+                entities.kill_player(victim_id, rules.respawn_time);
+                info!(target: "combat", player = victim_id, killer = shooter_id, "eliminated");
+                kills_to_award.push(shooter_id);
             }
-            if killed { kills_to_award.push(shooter_id); }
+        }
+    }
+    
+    // Small arms barely scratch vehicles; destruction is resolved in systems::vehicles.
+    for (shooter_id, vehicle_id) in vehicle_hits {
+        if !rules.friendly_fire && entities.friendly_vehicle(shooter_id, vehicle_id) { continue; }
+        if let Some(vehicle) = entities.vehicles.get(&vehicle_id) {
+            let damage = rules.damage_per_hit * vehicle.kind.small_arms_factor();
+            entities.damage_vehicle(vehicle_id, damage, shooter_id);
         }
     }
     
     // 4. Scores
     for shooter_id in kills_to_award {
-        if let Some(shooter) = players.get_mut(&shooter_id) { shooter.score_kills += 1; }
+        entities.award_kill(shooter_id);
+    }
+}
+
+/// Distance along a normalized ray to where it passes within `radius` of `center`.
+pub fn ray_sphere_distance(
+    origin: (f32, f32, f32),
+    dir: (f32, f32, f32),
+    center: (f32, f32, f32),
+    radius: f32,
+) -> Option<f32> {
+    let oc = (center.0 - origin.0, center.1 - origin.1, center.2 - origin.2);
+    let projection = oc.0 * dir.0 + oc.1 * dir.1 + oc.2 * dir.2;
+    if projection < 0.0 {
+        return None;
+    }
+    let dist_sq = (oc.0 * oc.0 + oc.1 * oc.1 + oc.2 * oc.2) - projection * projection;
+    if dist_sq < radius * radius {
+        Some(projection)
+    } else {
+        None
     }
 }
\ No newline at end of file
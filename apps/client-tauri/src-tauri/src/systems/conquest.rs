@@ -1,17 +1,19 @@
 // apps/client-tauri/src-tauri/src/systems/conquest.rs
 
-use std::collections::HashMap;
+use tracing::{info, trace};
 
-use crate::player::Player;
+use crate::config::Rules;
+use crate::entity::EntityStore;
 use crate::protocol::TeamId;
 use crate::sim::FlagZone;
 
 /// Run conquest capture + ticket bleed for this tick.
 pub fn update_conquest(
     flags: &mut Vec<FlagZone>,
-    players: &HashMap<u32, Player>,
+    entities: &EntityStore,
     tickets_a: &mut f32,
     tickets_b: &mut f32,
+    rules: &Rules,
     dt: f32,
 ) {
     if dt <= 0.0 {
@@ -19,11 +21,8 @@ pub fn update_conquest(
         return;
     }
 
-    // Tweak these to taste.
-    const CAPTURE_SPEED: f32 = 0.25;        // progress per second
-    const DECAY_SPEED: f32 = 0.10;          // decay when empty / contested
+    // Speeds and bleed come from the server config.
     const CAPTURE_THRESHOLD: f32 = 1.0;     // when |capture| >= 1.0, flip owner
-    const TICKET_BLEED_PER_FLAG: f32 = 0.5; // tickets per second per extra flag
 
     // === 1) Update capture progress for each flag ===
     for flag in flags.iter_mut() {
@@ -31,18 +30,19 @@ pub fn update_conquest(
         let mut count_b = 0u32;
 
         // Count players inside radius by team.
-        for p in players.values() {
+        for (id, p) in entities.players.iter() {
             if p.is_dead {
                 continue;
             }
+            let Some(t) = entities.transforms.get(id) else { continue };
 
-            let dx = p.transform.x - flag.x;
-            let dy = p.transform.y - flag.y;
-            let dz = p.transform.z - flag.z;
+            let dx = t.x - flag.x;
+            let dy = t.y - flag.y;
+            let dz = t.z - flag.z;
             let dist_sq = dx * dx + dy * dy + dz * dz;
 
             if dist_sq <= flag.radius * flag.radius {
-                match p.team {
+                match entities.teams.get(id).copied().unwrap_or(TeamId::None) {
                     TeamId::TeamA => count_a += 1,
                     TeamId::TeamB => count_b += 1,
                     _ => {}
@@ -52,9 +52,16 @@ pub fn update_conquest(
 
         // Debug: if anyone is in the zone, log counts once per tick.
         if count_a > 0 || count_b > 0 {
-            println!(
-                "[CONQUEST-TRACE] Flag {} players in radius: A={} B={} (pos=({:.1},{:.1},{:.1}), r={:.1})",
-                flag.id, count_a, count_b, flag.x, flag.y, flag.z, flag.radius
+            trace!(
+                target: "conquest",
+                flag = flag.id,
+                a = count_a,
+                b = count_b,
+                x = flag.x,
+                y = flag.y,
+                z = flag.z,
+                radius = flag.radius,
+                "players in radius"
             );
         }
 
@@ -63,25 +70,25 @@ pub fn update_conquest(
         // Decide capture direction.
         let delta = if count_a > 0 && count_b == 0 {
             // Team A capturing.
-            CAPTURE_SPEED * dt
+            rules.capture_speed * dt
         } else if count_b > 0 && count_a == 0 {
             // Team B capturing.
-            -CAPTURE_SPEED * dt
+            -rules.capture_speed * dt
         } else if count_a == 0 && count_b == 0 {
             // No one here: decay toward 0.
             if flag.capture > 0.0 {
-                -DECAY_SPEED * dt
+                -rules.decay_speed * dt
             } else if flag.capture < 0.0 {
-                DECAY_SPEED * dt
+                rules.decay_speed * dt
             } else {
                 0.0
             }
         } else {
             // Contested: decay toward 0.
             if flag.capture > 0.0 {
-                -DECAY_SPEED * dt
+                -rules.decay_speed * dt
             } else if flag.capture < 0.0 {
-                DECAY_SPEED * dt
+                rules.decay_speed * dt
             } else {
                 0.0
             }
@@ -99,9 +106,14 @@ pub fn update_conquest(
 
         // Debug: show capture progress if it's doing anything noticeable.
         if flag.capture.abs() > 0.01 {
-            println!(
-                "[CONQUEST-TRACE] Flag {} capture={:.2} owner={:?} (A={}, B={})",
-                flag.id, flag.capture, flag.owner, count_a, count_b
+            trace!(
+                target: "conquest",
+                flag = flag.id,
+                capture = flag.capture,
+                owner = ?flag.owner,
+                a = count_a,
+                b = count_b,
+                "capture progress"
             );
         }
 
@@ -116,13 +128,13 @@ pub fn update_conquest(
         if old_owner != flag.owner {
             match flag.owner {
                 TeamId::TeamA => {
-                    println!("[CONQUEST] Flag {} captured by Team A", flag.id);
+                    info!(target: "conquest", flag = flag.id, "captured by Team A");
                 }
                 TeamId::TeamB => {
-                    println!("[CONQUEST] Flag {} captured by Team B", flag.id);
+                    info!(target: "conquest", flag = flag.id, "captured by Team B");
                 }
                 _ => {
-                    println!("[CONQUEST] Flag {} became neutral", flag.id);
+                    info!(target: "conquest", flag = flag.id, "became neutral");
                 }
             }
         }
@@ -142,14 +154,14 @@ pub fn update_conquest(
 
     if owned_a > owned_b {
         let advantage = (owned_a - owned_b) as f32;
-        let bleed = advantage * TICKET_BLEED_PER_FLAG * dt;
+        let bleed = advantage * rules.ticket_bleed_per_flag * dt;
         *tickets_b -= bleed;
         if *tickets_b < 0.0 {
             *tickets_b = 0.0;
         }
     } else if owned_b > owned_a {
         let advantage = (owned_b - owned_a) as f32;
-        let bleed = advantage * TICKET_BLEED_PER_FLAG * dt;
+        let bleed = advantage * rules.ticket_bleed_per_flag * dt;
         *tickets_a -= bleed;
         if *tickets_a < 0.0 {
             *tickets_a = 0.0;
@@ -0,0 +1,232 @@
+// apps/client-tauri/src-tauri/src/systems/environment.rs
+use tracing::{debug, info};
+use crate::config::Rules;
+use crate::entity::{EntityId, EntityStore};
+use crate::sim::{KillVolume, WorldBounds};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnvironmentDeath {
+    Fall,
+    OutOfBounds,
+    KillVolume(u32),
+}
+
+/// Fall damage, world bounds and kill volumes.
+/// Environment deaths go to the last attacker if they hit the victim recently,
+/// otherwise they count as a suicide.
+pub fn update(
+    entities: &mut EntityStore,
+    bounds: &WorldBounds,
+    kill_volumes: &[KillVolume],
+    rules: &Rules,
+    dt: f32,
+) {
+    let EntityStore { players, transforms, healths, .. } = entities;
+    let mut deaths: Vec<EntityId> = Vec::new();
+    let mut kills_to_award: Vec<EntityId> = Vec::new();
+
+    for (id, player) in players.iter_mut() {
+        if player.last_attacker.is_some() {
+            player.last_attacker_timer -= dt;
+            if player.last_attacker_timer <= 0.0 {
+                player.last_attacker = None;
+                player.last_attacker_timer = 0.0;
+            }
+        }
+
+        if player.is_dead {
+            player.landing_speed = None;
+            continue;
+        }
+
+        let Some(t) = transforms.get(id) else { continue };
+        let mut cause = None;
+
+        // 1. Fall damage from impact velocity.
+        if let (Some(speed), Some(health)) = (player.landing_speed.take(), healths.get_mut(id)) {
+            if speed > rules.safe_fall_speed {
+                let damage = (speed - rules.safe_fall_speed) * rules.fall_damage_per_speed;
+                health.current -= damage;
+                debug!(
+                    target: "combat",
+                    player = id,
+                    damage,
+                    impact = speed,
+                    hp = health.current,
+                    "fall damage"
+                );
+                if health.current <= 0.0 {
+                    cause = Some(EnvironmentDeath::Fall);
+                }
+            }
+        }
+
+        // 2. World bounds: the floor of last resort.
+        if cause.is_none() && !inside_bounds(bounds, t.x, t.y, t.z) {
+            cause = Some(EnvironmentDeath::OutOfBounds);
+        }
+
+        // 3. Map-defined kill volumes.
+        if cause.is_none() {
+            cause = kill_volumes
+                .iter()
+                .find(|v| inside_volume(v, t.x, t.y, t.z))
+                .map(|v| EnvironmentDeath::KillVolume(v.id));
+        }
+
+        let Some(cause) = cause else { continue };
+
+        deaths.push(*id);
+        match player.last_attacker.take() {
+            Some(attacker_id) if attacker_id != *id => {
+                info!(target: "combat", player = id, ?cause, killer = attacker_id, "died");
+                kills_to_award.push(attacker_id);
+            }
+            _ => {
+                info!(target: "combat", player = id, ?cause, "died, suicide");
+            }
+        }
+    }
+
+    for id in deaths {
+        entities.kill_player(id, rules.respawn_time);
+    }
+    for attacker_id in kills_to_award {
+        entities.award_kill(attacker_id);
+    }
+}
+
+fn inside_bounds(b: &WorldBounds, x: f32, y: f32, z: f32) -> bool {
+    x >= b.min_x && x <= b.max_x && y >= b.min_y && y <= b.max_y && z >= b.min_z && z <= b.max_z
+}
+
+fn inside_volume(v: &KillVolume, x: f32, y: f32, z: f32) -> bool {
+    x >= v.min_x && x <= v.max_x && y >= v.min_y && y <= v.max_y && z >= v.min_z && z <= v.max_z
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    use crate::protocol::TeamId;
+    use crate::systems::combat::ATTACKER_CREDIT_WINDOW;
+    use crate::systems::movement::{self, MovementAuthority};
+
+    const DT: f32 = 0.05;
+
+    fn bounds() -> WorldBounds {
+        WorldBounds { min_x: -10.0, min_y: -5.0, min_z: -10.0, max_x: 10.0, max_y: 50.0, max_z: 10.0 }
+    }
+
+    fn pit() -> KillVolume {
+        KillVolume { id: 7, min_x: 4.0, min_y: -1.0, min_z: 4.0, max_x: 6.0, max_y: 1.0, max_z: 6.0 }
+    }
+
+    /// Two players on the ground at the origin.
+    fn setup() -> (EntityStore, EntityId, EntityId) {
+        let mut entities = EntityStore::new();
+        let victim = entities.spawn_player(1, "victim", TeamId::TeamA);
+        let attacker = entities.spawn_player(2, "attacker", TeamId::TeamB);
+        for id in [victim, attacker] {
+            entities.transforms.get_mut(&id).unwrap().y = 0.0;
+        }
+        (entities, victim, attacker)
+    }
+
+    fn step(entities: &mut EntityStore) {
+        update(entities, &bounds(), &[pit()], &Rules::default(), DT);
+    }
+
+    fn move_to(entities: &mut EntityStore, id: EntityId, x: f32, y: f32, z: f32) {
+        let t = entities.transforms.get_mut(&id).unwrap();
+        (t.x, t.y, t.z) = (x, y, z);
+    }
+
+    #[test]
+    fn fall_damage_scales_with_impact_speed() {
+        let rules = Rules::default();
+        let (mut entities, victim, _) = setup();
+
+        entities.players.get_mut(&victim).unwrap().landing_speed = Some(rules.safe_fall_speed);
+        step(&mut entities);
+        assert_eq!(entities.healths[&victim].current, 100.0);
+        assert_eq!(entities.players[&victim].landing_speed, None);
+
+        entities.players.get_mut(&victim).unwrap().landing_speed = Some(rules.safe_fall_speed + 4.0);
+        step(&mut entities);
+        assert_eq!(entities.healths[&victim].current, 100.0 - 4.0 * rules.fall_damage_per_speed);
+        assert!(!entities.players[&victim].is_dead);
+
+        entities.players.get_mut(&victim).unwrap().landing_speed = Some(100.0);
+        step(&mut entities);
+        assert!(entities.players[&victim].is_dead);
+        assert_eq!(entities.scores[&victim].deaths, 1);
+    }
+
+    #[test]
+    fn falling_from_a_height_hurts() {
+        let (mut entities, victim, _) = setup();
+        move_to(&mut entities, victim, 0.0, 10.0, 0.0);
+        for frame in 1..60 {
+            movement::update(&mut entities, &HashMap::new(), MovementAuthority::Server, DT, frame);
+            step(&mut entities);
+        }
+        // ~22 u/s on impact.
+        let health = entities.healths[&victim].current;
+        assert!(health < 100.0 && health > 0.0, "{}", health);
+    }
+
+    #[test]
+    fn leaving_the_bounds_or_entering_a_kill_volume_is_fatal() {
+        let (mut entities, victim, attacker) = setup();
+        move_to(&mut entities, victim, 0.0, -6.0, 0.0);
+        move_to(&mut entities, attacker, 5.0, 0.0, 5.0);
+        step(&mut entities);
+        assert!(entities.players[&victim].is_dead);
+        assert!(entities.players[&attacker].is_dead);
+
+        let (mut entities, victim, _) = setup();
+        move_to(&mut entities, victim, 9.9, 0.0, 5.0);
+        step(&mut entities);
+        assert!(!entities.players[&victim].is_dead);
+        move_to(&mut entities, victim, 10.1, 0.0, 5.0);
+        step(&mut entities);
+        assert!(entities.players[&victim].is_dead);
+    }
+
+    #[test]
+    fn recent_attackers_get_credit_for_environment_deaths() {
+        let (mut entities, victim, attacker) = setup();
+        let player = entities.players.get_mut(&victim).unwrap();
+        player.last_attacker = Some(attacker);
+        player.last_attacker_timer = ATTACKER_CREDIT_WINDOW;
+        step(&mut entities);
+
+        move_to(&mut entities, victim, 5.0, 0.0, 5.0);
+        step(&mut entities);
+        assert!(entities.players[&victim].is_dead);
+        assert_eq!(entities.scores[&attacker].kills, 1);
+        assert_eq!(entities.players[&victim].last_attacker, None);
+    }
+
+    #[test]
+    fn attacker_credit_runs_out() {
+        let (mut entities, victim, attacker) = setup();
+        let player = entities.players.get_mut(&victim).unwrap();
+        player.last_attacker = Some(attacker);
+        player.last_attacker_timer = ATTACKER_CREDIT_WINDOW;
+        let ticks = (ATTACKER_CREDIT_WINDOW / DT).ceil() as usize + 1;
+        for _ in 0..ticks {
+            step(&mut entities);
+        }
+        assert_eq!(entities.players[&victim].last_attacker, None);
+
+        // Now it is just a suicide.
+        move_to(&mut entities, victim, 5.0, 0.0, 5.0);
+        step(&mut entities);
+        assert!(entities.players[&victim].is_dead);
+        assert_eq!(entities.scores[&attacker].kills, 0);
+        assert_eq!(entities.scores[&victim].deaths, 1);
+    }
+}
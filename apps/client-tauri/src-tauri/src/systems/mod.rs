@@ -1,4 +1,8 @@
 // apps/client-tauri/src-tauri/src/systems/mod.rs
 pub mod movement;
+pub mod validation;
 pub mod combat;
-pub mod conquest;
\ No newline at end of file
+pub mod conquest;
+pub mod environment;
+pub mod projectiles;
+pub mod vehicles;
\ No newline at end of file
@@ -2,26 +2,52 @@
 use std::collections::HashMap;
 use std::f32::consts::PI;
 
+use serde::{Deserialize, Serialize};
+use tracing::{info, trace};
+
+use crate::entity::EntityStore;
 use crate::player::Player;
-use crate::protocol::ClientMessage;
+use crate::protocol::{ClientAxes, ClientMessage, Transform};
 
 pub const MOVE_SPEED: f32 = 10.0;
 pub const AIR_SPEED_FACTOR: f32 = 0.6;
 pub const GRAVITY: f32 = -25.0;
 pub const JUMP_FORCE: f32 = 9.0;
 const MOUSE_SENSITIVITY: f32 = 0.002;
-const SPRINT_MULTIPLIER: f32 = 1.5;
+pub const SPRINT_MULTIPLIER: f32 = 1.5;
+pub const GROUND_HEIGHT: f32 = 0.0;
+// Match the client's forgiving ground check.
+pub const GROUND_TOLERANCE: f32 = 0.05;
 pub const MOVEMENT_VERSION: &str = "movement-v1.0.0";
+// Longest gap between client state updates that still counts towards
+// the distance the next one may cover.
+pub const MAX_UPDATE_WINDOW: f32 = 0.5;
+
+/// Who owns continuous movement.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MovementAuthority {
+    // Server integrates movement from `ClientMessage::Input` axes.
+    Server,
+    // Clients report their own transform via `ClientMessage::StateUpdate`;
+    // the server only validates it.
+    Client,
+}
 
 fn print_versions() {
-    println!("Server movement version: {}", MOVEMENT_VERSION);
+    info!(target: "movement", version = MOVEMENT_VERSION, "server movement");
+}
+
+pub fn is_grounded(transform: &Transform) -> bool {
+    transform.y <= GROUND_HEIGHT + GROUND_TOLERANCE
 }
 
 /// Server-side movement update.
-/// `input_map` holds the latest `ClientMessage` per player.
+/// `input_map` holds the latest `ClientMessage` per connection.
 pub fn update(
-    players: &mut HashMap<u32, Player>,
+    entities: &mut EntityStore,
     input_map: &HashMap<u32, ClientMessage>,
+    authority: MovementAuthority,
     dt: f32,
     frame_count: u64,
 ) {
@@ -30,67 +56,239 @@ pub fn update(
         print_versions();
     }
 
+    let EntityStore { players, transforms, .. } = entities;
+
     for (id, player) in players.iter_mut() {
-        if player.is_dead {
+        // Seated players are moved by systems::vehicles.
+        if player.is_dead || player.vehicle_id.is_some() {
             continue;
         }
+        let Some(transform) = transforms.get_mut(id) else { continue };
+
+        let was_airborne = !is_grounded(transform);
+        if authority == MovementAuthority::Client {
+            player.since_update += dt;
+        }
 
-        // Look up the latest message from this player
-        if let Some(msg) = input_map.get(id) {
-            // Only handle "input" messages here. Fire / spawn are handled elsewhere.
-            if let ClientMessage::Input { tick: _tick, axes } = msg {
-                // Axes from TS schema
-                let mut fwd = axes.forward;
-                let right = axes.right;
-
-                // You can later map `axes.reload` to "sprint" or similar.
-                let sprint = axes.reload;
-
-                // Match client prediction logic: client uses `forward = -InputState.moveY[id]`
-                fwd = -fwd;
-
-                // Mouse look: client sends absolute yaw/pitch.
-                player.transform.yaw = axes.yaw;
-                player.transform.pitch = axes.pitch;
-                player.transform.pitch = player.transform.pitch.clamp(
-                    -PI / 2.0 + 0.1,
-                    PI / 2.0 - 0.1,
-                );
-
-                // Movement vectors based on yaw.
-                let yaw = player.transform.yaw;
-                let speed = if sprint {
-                    MOVE_SPEED * SPRINT_MULTIPLIER
-                } else {
-                    MOVE_SPEED
-                };
-
-                // Forward (W/S) direction
-                let vec_fwd_x = yaw.sin();
-                let vec_fwd_z = yaw.cos();
-
-                // Right (D/A) direction
-                let vec_right_x = yaw.cos();
-                let vec_right_z = -yaw.sin();
-
-                let move_x = (vec_fwd_x * fwd) + (vec_right_x * right);
-                let move_z = (vec_fwd_z * fwd) + (vec_right_z * right);
-
-                let len = (move_x * move_x + move_z * move_z).sqrt();
-                if len > 0.0 {
-                    player.transform.x += (move_x / len) * speed * dt;
-                    player.transform.z += (move_z / len) * speed * dt;
-
-                    if frame_count % 30 == 0 {
-                        println!(
-                            "[PHYSICS] Player {} Moved: {:.2}, {:.2}",
-                            id, player.transform.x, player.transform.z
-                        );
-                    }
+        // Look up the latest message from this player's connection.
+        // Only movement messages are handled here. Fire / spawn are handled elsewhere.
+        match (authority, input_map.get(&player.conn_id)) {
+            (MovementAuthority::Server, Some(ClientMessage::Input { axes, .. })) => {
+                apply_input(player, transform, axes, dt, frame_count);
+            }
+            (
+                MovementAuthority::Client,
+                Some(ClientMessage::StateUpdate { tick, x, y, z, yaw, pitch, sprint }),
+            ) => {
+                // The latest update stays in the input map; only apply it once.
+                // Stamina waits for the next one too, which covers the whole gap.
+                if player.last_update_tick.is_none_or(|t| *tick > t) {
+                    player.last_update_tick = Some(*tick);
+                    // Measured from the previous accepted update rather than
+                    // the last tick, so a late packet isn't a speed spike.
+                    let elapsed = player.since_update.min(MAX_UPDATE_WINDOW);
+                    player.since_update = 0.0;
+                    player.update_window = Some(elapsed);
+                    apply_state_update(player, transform, (*x, *y, *z), *yaw, *pitch, *sprint, elapsed);
                 }
+            }
+            _ => {
+                // No movement input this tick: still regenerate stamina.
+                player.update_stamina(false, dt);
+            }
+        }
 
-                // Jump / gravity logic can be added here later using axes.jump, GRAVITY, JUMP_FORCE, etc.
+        // In client-authoritative mode the client runs its own gravity.
+        if authority == MovementAuthority::Server {
+            player.velocity.1 += GRAVITY * dt;
+            transform.y += player.velocity.1 * dt;
+            if transform.y <= GROUND_HEIGHT {
+                if player.velocity.1 < 0.0 && was_airborne {
+                    player.landing_speed = Some(-player.velocity.1);
+                }
+                transform.y = GROUND_HEIGHT;
+                player.velocity.1 = 0.0;
             }
         }
     }
 }
+
+/// Integrate one tick of movement from client axes (server-authoritative mode).
+fn apply_input(
+    player: &mut Player,
+    transform: &mut Transform,
+    axes: &ClientAxes,
+    dt: f32,
+    frame_count: u64,
+) {
+    // Match client prediction logic: client uses `forward = -InputState.moveY[id]`
+    let fwd = -axes.forward;
+    let right = axes.right;
+
+    // Mouse look: client sends absolute yaw/pitch.
+    transform.yaw = axes.yaw;
+    transform.pitch = clamp_pitch(axes.pitch);
+
+    // Movement vectors based on yaw.
+    let yaw = transform.yaw;
+
+    // Forward (W/S) direction
+    let vec_fwd_x = yaw.sin();
+    let vec_fwd_z = yaw.cos();
+
+    // Right (D/A) direction
+    let vec_right_x = yaw.cos();
+    let vec_right_z = -yaw.sin();
+
+    let move_x = (vec_fwd_x * fwd) + (vec_right_x * right);
+    let move_z = (vec_fwd_z * fwd) + (vec_right_z * right);
+
+    let len = (move_x * move_x + move_z * move_z).sqrt();
+    let grounded = is_grounded(transform);
+
+    // Sprint only drains stamina while actually moving.
+    let sprint = player.update_stamina(axes.sprint && len > 0.0, dt);
+    let mut speed = if sprint {
+        MOVE_SPEED * SPRINT_MULTIPLIER
+    } else {
+        MOVE_SPEED
+    };
+    if !grounded {
+        speed *= AIR_SPEED_FACTOR;
+    }
+
+    if len > 0.0 {
+        transform.x += (move_x / len) * speed * dt;
+        transform.z += (move_z / len) * speed * dt;
+
+        if frame_count % 30 == 0 {
+            trace!(target: "movement", player = player.id, x = transform.x, z = transform.z, "moved");
+        }
+    }
+
+    // Only allow jump if not already shooting up (same rule as the client).
+    if axes.jump && grounded && player.velocity.1 <= 0.1 {
+        player.velocity.1 = JUMP_FORCE;
+    }
+}
+
+/// Take a client-reported transform (client-authoritative mode).
+/// Validation runs afterwards and pulls it back if it breaks the limits.
+/// `dt` is the time since the previous accepted update.
+fn apply_state_update(
+    player: &mut Player,
+    transform: &mut Transform,
+    pos: (f32, f32, f32),
+    yaw: f32,
+    pitch: f32,
+    sprint: bool,
+    dt: f32,
+) {
+    let (x, y, z) = pos;
+    let (dx, dy, dz) = (
+        x - transform.x,
+        y - transform.y,
+        z - transform.z,
+    );
+    let moved = dx != 0.0 || dz != 0.0;
+    let was_airborne = !is_grounded(transform);
+    let prev_vy = player.velocity.1;
+
+    // Stamina is still server-owned: a client claiming sprint while
+    // exhausted gets validated against walking speed.
+    player.update_stamina(sprint && moved, dt);
+
+    // Keep velocity in sync so the rest of the sim can reason about it.
+    if dt > 0.0 {
+        player.velocity = (dx / dt, dy / dt, dz / dt);
+    }
+
+    transform.x = x;
+    transform.y = y;
+    transform.z = z;
+    transform.yaw = yaw;
+    transform.pitch = clamp_pitch(pitch);
+
+    // The landing tick itself covers only part of the fall, so use the
+    // vertical speed from the tick before.
+    if was_airborne && is_grounded(transform) && prev_vy < 0.0 {
+        player.landing_speed = Some(-prev_vy);
+    }
+}
+
+fn clamp_pitch(pitch: f32) -> f32 {
+    pitch.clamp(-PI / 2.0 + 0.1, PI / 2.0 - 0.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::EntityId;
+    use crate::protocol::TeamId;
+
+    const DT: f32 = 0.05;
+
+    fn setup() -> (EntityStore, EntityId) {
+        let mut entities = EntityStore::new();
+        let id = entities.spawn_player(1, "tester", TeamId::TeamA);
+        entities.transforms.get_mut(&id).unwrap().y = GROUND_HEIGHT;
+        (entities, id)
+    }
+
+    fn state_update(tick: u32, x: f32) -> HashMap<u32, ClientMessage> {
+        let msg = ClientMessage::StateUpdate { tick, x, y: 0.0, z: 0.0, yaw: 0.0, pitch: 0.0, sprint: false };
+        HashMap::from([(1, msg)])
+    }
+
+    fn walk_forward() -> HashMap<u32, ClientMessage> {
+        let axes = ClientAxes {
+            forward: -1.0,
+            right: 0.0,
+            jump: false,
+            shoot: false,
+            reload: false,
+            sprint: false,
+            yaw: 0.0,
+            pitch: 0.0,
+        };
+        HashMap::from([(1, ClientMessage::Input { tick: 1, axes })])
+    }
+
+    #[test]
+    fn each_mode_only_takes_its_own_messages() {
+        let (mut entities, id) = setup();
+        update(&mut entities, &state_update(1, 3.0), MovementAuthority::Server, DT, 1);
+        assert_eq!(entities.transforms[&id].x, 0.0);
+        update(&mut entities, &walk_forward(), MovementAuthority::Client, DT, 2);
+        assert_eq!(entities.transforms[&id].z, 0.0);
+
+        update(&mut entities, &walk_forward(), MovementAuthority::Server, DT, 3);
+        assert!((entities.transforms[&id].z - MOVE_SPEED * DT).abs() < 1e-5);
+        update(&mut entities, &state_update(1, 3.0), MovementAuthority::Client, DT, 4);
+        assert_eq!(entities.transforms[&id].x, 3.0);
+    }
+
+    #[test]
+    fn client_updates_cover_the_time_since_the_last_one() {
+        let (mut entities, id) = setup();
+        update(&mut entities, &state_update(1, 0.5), MovementAuthority::Client, DT, 1);
+        assert_eq!(entities.players.get_mut(&id).unwrap().update_window.take(), Some(DT));
+
+        // Two ticks where the same update is still the latest one.
+        update(&mut entities, &state_update(1, 0.5), MovementAuthority::Client, DT, 2);
+        update(&mut entities, &state_update(1, 0.5), MovementAuthority::Client, DT, 3);
+        assert_eq!(entities.players[&id].update_window, None);
+
+        update(&mut entities, &state_update(2, 2.0), MovementAuthority::Client, DT, 4);
+        let player = &entities.players[&id];
+        assert!((player.update_window.unwrap() - DT * 3.0).abs() < 1e-6);
+        assert!((player.velocity.0 - 1.5 / (DT * 3.0)).abs() < 1e-3);
+
+        // A client that goes quiet for long gets no more than the cap.
+        for frame in 5..40 {
+            update(&mut entities, &state_update(2, 2.0), MovementAuthority::Client, DT, frame);
+        }
+        update(&mut entities, &state_update(3, 2.0), MovementAuthority::Client, DT, 40);
+        assert_eq!(entities.players[&id].update_window, Some(MAX_UPDATE_WINDOW));
+    }
+}
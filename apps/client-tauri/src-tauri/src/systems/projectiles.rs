@@ -0,0 +1,469 @@
+// apps/client-tauri/src-tauri/src/systems/projectiles.rs
+use std::collections::HashMap;
+
+use tracing::{debug, info};
+
+use crate::config::Rules;
+use crate::entity::{EntityId, EntityStore};
+use crate::projectile::Projectile;
+use crate::protocol::{ClientMessage, ProjectileKind, TeamId, Transform};
+use crate::sim::{Collider, WorldBounds};
+use crate::systems::combat::ATTACKER_CREDIT_WINDOW;
+use crate::systems::movement::{GRAVITY, GROUND_HEIGHT};
+use crate::vehicle::hull_center;
+
+const PROJECTILE_RADIUS: f32 = 0.1;
+// Spawn height above the thrower's feet (same eye offset as hitscan).
+const THROW_HEIGHT: f32 = 0.6;
+// Extra upward speed for hand-thrown grenades so they arc.
+const THROW_LOFT: f32 = 4.0;
+// Horizontal speed kept on each ground contact.
+const GROUND_FRICTION: f32 = 0.7;
+// Below this speed a projectile on the ground comes to rest.
+const REST_SPEED: f32 = 0.5;
+// Explosions aim at roughly chest height.
+const TARGET_HEIGHT: f32 = 0.9;
+
+struct Launch {
+    kind: ProjectileKind,
+    owner: EntityId,
+    team: TeamId,
+    position: (f32, f32, f32),
+    velocity: (f32, f32, f32),
+}
+
+/// Spawn projectiles from `Fire` messages, move them, and detonate expired fuses.
+pub fn update(
+    entities: &mut EntityStore,
+    input_map: &HashMap<u32, ClientMessage>,
+    colliders: &[Collider],
+    bounds: &WorldBounds,
+    rules: &Rules,
+    dt: f32,
+) {
+    // 1. Spawn
+    let mut launches: Vec<Launch> = Vec::new();
+    for (id, player) in entities.players.iter_mut() {
+        if player.throw_cooldown > 0.0 {
+            player.throw_cooldown -= dt;
+        }
+        if player.is_dead || player.vehicle_id.is_some() || player.throw_cooldown > 0.0 {
+            continue;
+        }
+
+        let Some(ClientMessage::Fire { tick, direction, weapon_id, .. }) = input_map.get(&player.conn_id) else {
+            continue;
+        };
+        let Some(kind) = ProjectileKind::from_weapon_id(*weapon_id) else {
+            continue;
+        };
+        let Some(t) = entities.transforms.get(id) else {
+            continue;
+        };
+        // The latest message stays in the input map; one projectile per message.
+        if player.last_throw_tick == Some(*tick) {
+            continue;
+        }
+        player.last_throw_tick = Some(*tick);
+        player.throw_cooldown = kind.cooldown();
+
+        // The direction was normalized by validate.rs; the origin is the
+        // player's own position rather than the reported one.
+        let speed = kind.launch_speed();
+        let mut velocity = (direction.x * speed, direction.y * speed, direction.z * speed);
+        if kind == ProjectileKind::Grenade {
+            velocity.1 += THROW_LOFT;
+        }
+
+        launches.push(Launch {
+            kind,
+            owner: *id,
+            team: entities.teams.get(id).copied().unwrap_or(TeamId::None),
+            position: (t.x, t.y + THROW_HEIGHT, t.z),
+            velocity,
+        });
+    }
+
+    for l in launches {
+        let id = entities.spawn_projectile(l.kind, l.owner, l.team, l.position, l.velocity);
+        debug!(target: "combat", player = l.owner, kind = ?l.kind, projectile = id, "launched");
+    }
+
+    // 2. Move + fuse
+    let mut detonated: Vec<(Projectile, (f32, f32, f32))> = Vec::new();
+    for (id, p) in entities.projectiles.iter_mut() {
+        let Some(t) = entities.transforms.get_mut(id) else { continue };
+        step(p, t, colliders, bounds, dt);
+        p.fuse -= dt;
+        if p.fuse <= 0.0 {
+            detonated.push((p.clone(), (t.x, t.y, t.z)));
+        }
+    }
+
+    // 3. Explode
+    for (p, position) in &detonated {
+        entities.despawn(p.id);
+        explode(p, *position, entities, colliders, rules);
+    }
+}
+
+/// Integrate one tick of gravity and bounce off the ground, world bounds and colliders.
+fn step(p: &mut Projectile, t: &mut Transform, colliders: &[Collider], bounds: &WorldBounds, dt: f32) {
+    let (px, py, pz) = (t.x, t.y, t.z);
+    let restitution = p.kind.restitution();
+
+    p.velocity.1 += GRAVITY * dt;
+    let (mut vx, mut vy, mut vz) = p.velocity;
+    let mut x = px + vx * dt;
+    let mut y = py + vy * dt;
+    let mut z = pz + vz * dt;
+
+    // Ground
+    let floor = GROUND_HEIGHT + PROJECTILE_RADIUS;
+    if y < floor {
+        y = floor;
+        if vy < 0.0 {
+            vy = -vy * restitution;
+        }
+        vx *= GROUND_FRICTION;
+        vz *= GROUND_FRICTION;
+        if (vx * vx + vy * vy + vz * vz).sqrt() < REST_SPEED {
+            vx = 0.0;
+            vy = 0.0;
+            vz = 0.0;
+        }
+    }
+
+    // World bounds act as walls.
+    if x < bounds.min_x || x > bounds.max_x {
+        x = x.clamp(bounds.min_x, bounds.max_x);
+        vx = -vx * restitution;
+    }
+    if z < bounds.min_z || z > bounds.max_z {
+        z = z.clamp(bounds.min_z, bounds.max_z);
+        vz = -vz * restitution;
+    }
+
+    // Map geometry: reflect along whichever axes we crossed into the box on.
+    for c in colliders {
+        if !inside_collider(c, x, y, z) {
+            continue;
+        }
+        if px < c.min_x || px > c.max_x {
+            x = px;
+            vx = -vx * restitution;
+        }
+        if py < c.min_y || py > c.max_y {
+            y = py;
+            vy = -vy * restitution;
+        }
+        if pz < c.min_z || pz > c.max_z {
+            z = pz;
+            vz = -vz * restitution;
+        }
+    }
+
+    t.x = x;
+    t.y = y;
+    t.z = z;
+    p.velocity = (vx, vy, vz);
+}
+
+/// Radial damage with linear falloff, blocked by map geometry.
+fn explode(
+    p: &Projectile,
+    position: (f32, f32, f32),
+    entities: &mut EntityStore,
+    colliders: &[Collider],
+    rules: &Rules,
+) {
+    let radius = p.kind.blast_radius();
+    let (ex, ey, ez) = position;
+    debug!(
+        target: "combat",
+        kind = ?p.kind,
+        projectile = p.id,
+        owner = p.owner,
+        x = ex,
+        y = ey,
+        z = ez,
+        "exploded"
+    );
+
+    // Work out who is in the blast first, then apply damage.
+    let blast = |target: (f32, f32, f32)| -> Option<f32> {
+        let (dx, dy, dz) = (target.0 - ex, target.1 - ey, target.2 - ez);
+        let dist = (dx * dx + dy * dy + dz * dz).sqrt();
+        if dist >= radius || !line_of_sight(colliders, position, target) {
+            return None;
+        }
+        Some(p.kind.max_damage() * (1.0 - dist / radius))
+    };
+
+    let mut player_hits: Vec<(EntityId, f32)> = Vec::new();
+    for (id, victim) in entities.players.iter() {
+        // Vehicle occupants are shielded by the hull.
+        if victim.is_dead || victim.vehicle_id.is_some() {
+            continue;
+        }
+        // The thrower is always caught by their own blast.
+        if !rules.friendly_fire && entities.teammates(p.owner, *id) {
+            continue;
+        }
+        let Some(t) = entities.transforms.get(id) else { continue };
+        if let Some(damage) = blast((t.x, t.y + TARGET_HEIGHT, t.z)) {
+            player_hits.push((*id, damage));
+        }
+    }
+
+    // Destruction itself is resolved in systems::vehicles.
+    let mut vehicle_hits: Vec<(EntityId, f32)> = Vec::new();
+    for vehicle in entities.vehicles.values().filter(|v| !v.is_destroyed) {
+        if !rules.friendly_fire && entities.friendly_vehicle(p.owner, vehicle.id) {
+            continue;
+        }
+        let Some(t) = entities.transforms.get(&vehicle.id) else { continue };
+        if let Some(damage) = blast(hull_center(t)) {
+            vehicle_hits.push((vehicle.id, damage));
+        }
+    }
+
+    let mut kills = 0u32;
+    for (id, damage) in player_hits {
+        let Some(hp) = entities.damage(id, damage) else { continue };
+        if id != p.owner {
+            if let Some(victim) = entities.players.get_mut(&id) {
+                victim.last_attacker = Some(p.owner);
+                victim.last_attacker_timer = ATTACKER_CREDIT_WINDOW;
+            }
+        }
+        debug!(target: "combat", player = id, damage, hp, "blast damage");
+
+        if hp <= 0.0 {
+            entities.kill_player(id, rules.respawn_time);
+            if id == p.owner {
+                info!(target: "combat", player = id, "killed themselves");
+            } else {
+                info!(target: "combat", player = id, killer = p.owner, "eliminated");
+                kills += 1;
+            }
+        }
+    }
+
+    for _ in 0..kills {
+        entities.award_kill(p.owner);
+    }
+
+    for (id, damage) in vehicle_hits {
+        entities.damage_vehicle(id, damage, p.owner);
+    }
+}
+
+/// True if the segment `from -> to` does not pass through any collider.
+pub fn line_of_sight(colliders: &[Collider], from: (f32, f32, f32), to: (f32, f32, f32)) -> bool {
+    colliders.iter().all(|c| !segment_hits_collider(c, from, to))
+}
+
+fn inside_collider(c: &Collider, x: f32, y: f32, z: f32) -> bool {
+    x >= c.min_x && x <= c.max_x && y >= c.min_y && y <= c.max_y && z >= c.min_z && z <= c.max_z
+}
+
+// Slab test: clip the segment against each axis of the box.
+fn segment_hits_collider(c: &Collider, from: (f32, f32, f32), to: (f32, f32, f32)) -> bool {
+    let axes = [
+        (from.0, to.0 - from.0, c.min_x, c.max_x),
+        (from.1, to.1 - from.1, c.min_y, c.max_y),
+        (from.2, to.2 - from.2, c.min_z, c.max_z),
+    ];
+
+    let mut t_min = 0.0f32;
+    let mut t_max = 1.0f32;
+    for (origin, delta, min, max) in axes {
+        if delta.abs() < f32::EPSILON {
+            if origin < min || origin > max {
+                return false;
+            }
+            continue;
+        }
+        let t1 = (min - origin) / delta;
+        let t2 = (max - origin) / delta;
+        t_min = t_min.max(t1.min(t2));
+        t_max = t_max.min(t1.max(t2));
+        if t_min > t_max {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projectile::WEAPON_ID_RIFLE_GRENADE;
+    use crate::protocol::Vec3;
+
+    const DT: f32 = 0.05;
+
+    fn open_bounds() -> WorldBounds {
+        WorldBounds { min_x: -100.0, min_y: -20.0, min_z: -100.0, max_x: 100.0, max_y: 100.0, max_z: 100.0 }
+    }
+
+    // A wall across the x axis, from x = 2 to x = 3.
+    fn wall() -> Collider {
+        Collider { min_x: 2.0, min_y: 0.0, min_z: -10.0, max_x: 3.0, max_y: 5.0, max_z: 10.0 }
+    }
+
+    fn grenade(velocity: (f32, f32, f32)) -> Projectile {
+        let kind = ProjectileKind::Grenade;
+        Projectile { id: 1, kind, owner: 0, velocity, fuse: kind.fuse() }
+    }
+
+    fn at(x: f32, y: f32, z: f32) -> Transform {
+        Transform { x, y, z, yaw: 0.0, pitch: 0.0 }
+    }
+
+    /// A thrower on team A, and targets on team B at the given spots on the ground.
+    fn setup(targets: &[(f32, f32)]) -> (EntityStore, EntityId, Vec<EntityId>) {
+        let mut entities = EntityStore::new();
+        let owner = entities.spawn_player(1, "thrower", TeamId::TeamA);
+        let t = entities.transforms.get_mut(&owner).unwrap();
+        (t.x, t.y, t.z) = (-50.0, 0.0, -50.0);
+        let victims = targets
+            .iter()
+            .enumerate()
+            .map(|(i, (x, z))| {
+                let id = entities.spawn_player(i as u32 + 2, "target", TeamId::TeamB);
+                let t = entities.transforms.get_mut(&id).unwrap();
+                (t.x, t.y, t.z) = (*x, 0.0, *z);
+                id
+            })
+            .collect();
+        (entities, owner, victims)
+    }
+
+    /// Drop a grenade on the floor at `x, z` and run until it goes off.
+    fn detonate(entities: &mut EntityStore, owner: EntityId, x: f32, z: f32, colliders: &[Collider]) {
+        let floor = GROUND_HEIGHT + PROJECTILE_RADIUS;
+        let id = entities.spawn_projectile(ProjectileKind::Grenade, owner, TeamId::TeamA, (x, floor, z), (0.0, 0.0, 0.0));
+        let ticks = (ProjectileKind::Grenade.fuse() / DT).ceil() as usize + 1;
+        for _ in 0..ticks {
+            update(entities, &HashMap::new(), colliders, &open_bounds(), &Rules::default(), DT);
+        }
+        assert!(!entities.projectiles.contains_key(&id));
+    }
+
+    fn falloff(distance: f32) -> f32 {
+        let kind = ProjectileKind::Grenade;
+        kind.max_damage() * (1.0 - distance / kind.blast_radius())
+    }
+
+    #[test]
+    fn bounces_off_colliders_and_the_ground() {
+        let mut p = grenade((20.0, 0.0, 0.0));
+        let mut t = at(1.5, 2.0, 0.0);
+        step(&mut p, &mut t, &[wall()], &open_bounds(), DT);
+        // Would have ended up inside the wall: stays out and comes back.
+        assert_eq!(t.x, 1.5);
+        assert!((p.velocity.0 + 20.0 * ProjectileKind::Grenade.restitution()).abs() < 1e-4);
+
+        let mut p = grenade((0.0, -10.0, 0.0));
+        let mut t = at(0.0, 0.2, 0.0);
+        step(&mut p, &mut t, &[], &open_bounds(), DT);
+        assert_eq!(t.y, GROUND_HEIGHT + PROJECTILE_RADIUS);
+        assert!(p.velocity.1 > 0.0);
+
+        // Without the wall it flies straight through.
+        let mut p = grenade((20.0, 0.0, 0.0));
+        let mut t = at(1.5, 2.0, 0.0);
+        step(&mut p, &mut t, &[], &open_bounds(), DT);
+        assert_eq!(t.x, 2.5);
+    }
+
+    #[test]
+    fn grenades_come_to_rest_on_the_ground() {
+        let mut p = grenade((3.0, 0.0, 0.0));
+        let mut t = at(0.0, 1.0, 0.0);
+        for _ in 0..100 {
+            step(&mut p, &mut t, &[], &open_bounds(), DT);
+        }
+        assert_eq!(p.velocity, (0.0, 0.0, 0.0));
+        assert_eq!(t.y, GROUND_HEIGHT + PROJECTILE_RADIUS);
+    }
+
+    #[test]
+    fn fuse_runs_out_then_it_explodes() {
+        let (mut entities, owner, _) = setup(&[]);
+        let id = entities.spawn_projectile(ProjectileKind::Grenade, owner, TeamId::TeamA, (0.0, 0.1, 0.0), (0.0, 0.0, 0.0));
+        let ticks = (ProjectileKind::Grenade.fuse() / DT).round() as usize;
+        for _ in 0..ticks - 1 {
+            update(&mut entities, &HashMap::new(), &[], &open_bounds(), &Rules::default(), DT);
+        }
+        assert!(entities.projectiles.contains_key(&id));
+        for _ in 0..2 {
+            update(&mut entities, &HashMap::new(), &[], &open_bounds(), &Rules::default(), DT);
+        }
+        assert!(!entities.projectiles.contains_key(&id));
+        assert!(!entities.kinds.contains_key(&id));
+    }
+
+    #[test]
+    fn damage_falls_off_with_distance() {
+        let (mut entities, owner, victims) = setup(&[(2.0, 0.0), (0.0, 5.0), (8.0, 0.0)]);
+        detonate(&mut entities, owner, 0.0, 0.0, &[]);
+
+        // Damage is aimed at chest height, from just above the floor.
+        let rise = TARGET_HEIGHT - PROJECTILE_RADIUS;
+        for (victim, flat) in victims.iter().zip([2.0f32, 5.0]) {
+            let expected = 100.0 - falloff((flat * flat + rise * rise).sqrt());
+            assert!((entities.healths[victim].current - expected).abs() < 1e-3);
+        }
+        // Out of range.
+        assert_eq!(entities.healths[&victims[2]].current, 100.0);
+        assert_eq!(entities.players[&victims[0]].last_attacker, Some(owner));
+    }
+
+    #[test]
+    fn walls_block_the_blast() {
+        let (mut entities, owner, victims) = setup(&[(4.0, 0.0), (-4.0, 0.0)]);
+        detonate(&mut entities, owner, 0.0, 0.0, &[wall()]);
+
+        assert_eq!(entities.healths[&victims[0]].current, 100.0);
+        assert!(entities.healths[&victims[1]].current < 100.0);
+
+        assert!(!line_of_sight(&[wall()], (0.0, 1.0, 0.0), (4.0, 1.0, 0.0)));
+        // Over the top of it.
+        assert!(line_of_sight(&[wall()], (0.0, 6.0, 0.0), (4.0, 6.0, 0.0)));
+    }
+
+    #[test]
+    fn close_blasts_kill_and_credit_the_thrower() {
+        let (mut entities, owner, victims) = setup(&[(0.5, 0.0)]);
+        entities.healths.get_mut(&victims[0]).unwrap().current = 50.0;
+        detonate(&mut entities, owner, 0.0, 0.0, &[]);
+        assert!(entities.players[&victims[0]].is_dead);
+        assert_eq!(entities.scores[&owner].kills, 1);
+    }
+
+    #[test]
+    fn launches_follow_the_fire_direction() {
+        let (mut entities, owner, _) = setup(&[]);
+        let fire = ClientMessage::Fire {
+            tick: 1,
+            origin: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+            direction: Vec3 { x: 1.0, y: 0.0, z: 0.0 },
+            weapon_id: WEAPON_ID_RIFLE_GRENADE,
+        };
+        let inputs = HashMap::from([(entities.players[&owner].conn_id, fire)]);
+        update(&mut entities, &inputs, &[], &open_bounds(), &Rules::default(), DT);
+
+        let p = entities.projectiles.values().next().expect("nothing launched");
+        assert!(p.velocity.0 > 0.0 && p.velocity.2 == 0.0, "{:?}", p.velocity);
+        // Launched from the thrower, not the reported origin.
+        let t = entities.transforms[&p.id];
+        assert!(t.x < -40.0 && t.z == -50.0, "{:?}", t);
+
+        // The same message is only one throw.
+        update(&mut entities, &inputs, &[], &open_bounds(), &Rules::default(), DT);
+        assert_eq!(entities.projectiles.len(), 1);
+    }
+}
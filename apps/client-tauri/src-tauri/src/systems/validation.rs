@@ -0,0 +1,383 @@
+// apps/client-tauri/src-tauri/src/systems/validation.rs
+use std::collections::VecDeque;
+
+use tracing::warn;
+
+use crate::entity::{EntityId, EntityStore};
+use crate::player::Player;
+use crate::protocol::Transform;
+use crate::systems::movement::{
+    self, MovementAuthority, AIR_SPEED_FACTOR, GRAVITY, GROUND_HEIGHT, GROUND_TOLERANCE, JUMP_FORCE,
+    MOVE_SPEED, SPRINT_MULTIPLIER,
+};
+
+pub const VEHICLE_MAX_SPEED: f32 = 25.0;
+// Any single-tick jump further than this is treated as a teleport.
+pub const MAX_TELEPORT_DISTANCE: f32 = 8.0;
+// Headroom on top of the allowed speed (dt jitter, float error).
+const SPEED_TOLERANCE: f32 = 1.25;
+const DISTANCE_SLACK: f32 = 0.05;
+// Client-reported jumps may lag the server's view of them by this much.
+const AIR_TIME_SLACK: f32 = 0.2;
+// How many recent violations SimState keeps around for inspection.
+pub const MAX_VIOLATION_LOG: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MovementState {
+    Walking,
+    Sprinting,
+    Airborne,
+    InVehicle,
+}
+
+impl MovementState {
+    pub fn of(player: &Player, transform: &Transform) -> Self {
+        if player.vehicle_id.is_some() {
+            MovementState::InVehicle
+        } else if !movement::is_grounded(transform) {
+            MovementState::Airborne
+        } else if player.is_sprinting {
+            MovementState::Sprinting
+        } else {
+            MovementState::Walking
+        }
+    }
+
+    /// Max horizontal speed (units per second) allowed in this state.
+    pub fn max_speed(self) -> f32 {
+        match self {
+            MovementState::Walking => MOVE_SPEED,
+            MovementState::Sprinting => MOVE_SPEED * SPRINT_MULTIPLIER,
+            // Air control is reduced, but a sprint-jump keeps some momentum.
+            MovementState::Airborne => MOVE_SPEED * SPRINT_MULTIPLIER * AIR_SPEED_FACTOR,
+            MovementState::InVehicle => VEHICLE_MAX_SPEED,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViolationKind {
+    // Moved faster than the state allows: clamped to the allowed distance.
+    Speed,
+    // Moved further than MAX_TELEPORT_DISTANCE: snapped back.
+    Teleport,
+    // Rose faster than a jump allows, or stayed up against gravity
+    // (client-reported movement): pulled back down.
+    Vertical,
+    // Reported a position under the floor: put back on it.
+    BelowGround,
+}
+
+#[derive(Debug, Clone)]
+pub struct MovementViolation {
+    pub player_id: EntityId,
+    pub frame: u64,
+    pub state: MovementState,
+    pub kind: ViolationKind,
+    pub distance: f32,
+    pub allowed: f32,
+}
+
+/// Highest climb allowed over `window` seconds that end `air_time` seconds
+/// after leaving the ground, following a jump's arc. Negative once the arc
+/// is past its top: the player has to be falling.
+fn max_rise(air_time: f32, window: f32) -> f32 {
+    let t = (air_time - window / 2.0 - AIR_TIME_SLACK).max(0.0);
+    let vy = JUMP_FORCE + GRAVITY * t;
+    vy * window + vy.abs() * window * (SPEED_TOLERANCE - 1.0) + DISTANCE_SLACK
+}
+
+/// Check every player's movement since the last tick against the limits for
+/// their current state. Offending moves are corrected in place and recorded.
+/// Client-reported movement is also checked against gravity, since the
+/// server doesn't integrate it.
+pub fn update(
+    entities: &mut EntityStore,
+    violations: &mut VecDeque<MovementViolation>,
+    authority: MovementAuthority,
+    dt: f32,
+    frame_count: u64,
+) {
+    let EntityStore { players, transforms, .. } = entities;
+
+    for (id, player) in players.iter_mut() {
+        let Some(transform) = transforms.get_mut(id) else { continue };
+        if player.is_dead {
+            player.last_valid_pos = (transform.x, transform.y, transform.z);
+            player.air_time = 0.0;
+            continue;
+        }
+        // A client update covers the time since the one before it.
+        let update = player.update_window.take();
+        let window = update.unwrap_or(dt);
+
+        let (lx, ly, lz) = player.last_valid_pos;
+        let dx = transform.x - lx;
+        let dy = transform.y - ly;
+        let dz = transform.z - lz;
+
+        let horizontal = (dx * dx + dz * dz).sqrt();
+        let total = (dx * dx + dy * dy + dz * dz).sqrt();
+
+        // A takeoff or landing tick is spent partly in each state, so allow
+        // whichever of the two is faster.
+        let before = MovementState::of(player, &Transform { x: lx, y: ly, z: lz, ..*transform });
+        let after = MovementState::of(player, transform);
+        let state = if before.max_speed() >= after.max_speed() { before } else { after };
+        let allowed = state.max_speed() * window.max(0.0) * SPEED_TOLERANCE + DISTANCE_SLACK;
+
+        let mut found = Vec::new();
+        if total > MAX_TELEPORT_DISTANCE {
+            transform.x = lx;
+            transform.y = ly;
+            transform.z = lz;
+            player.velocity = (0.0, 0.0, 0.0);
+            found.push((ViolationKind::Teleport, total, MAX_TELEPORT_DISTANCE));
+        } else {
+            if horizontal > allowed {
+                let scale = allowed / horizontal;
+                transform.x = lx + dx * scale;
+                transform.z = lz + dz * scale;
+                found.push((ViolationKind::Speed, horizontal, allowed));
+            }
+            // Between updates a client-reported player stays put, so only
+            // the updates themselves are held to the arc.
+            if authority == MovementAuthority::Client && update.is_some() {
+                let rise = max_rise(player.air_time + dt, window.max(0.0));
+                if dy > rise && !movement::is_grounded(transform) {
+                    transform.y = (ly + rise).max(GROUND_HEIGHT);
+                    player.velocity.1 = player.velocity.1.min(0.0);
+                    found.push((ViolationKind::Vertical, dy, rise));
+                }
+                if transform.y < GROUND_HEIGHT - GROUND_TOLERANCE {
+                    found.push((ViolationKind::BelowGround, GROUND_HEIGHT - transform.y, GROUND_TOLERANCE));
+                    transform.y = GROUND_HEIGHT;
+                }
+            }
+        }
+        if authority == MovementAuthority::Client {
+            player.air_time = if movement::is_grounded(transform) { 0.0 } else { player.air_time + dt };
+        }
+
+        for (kind, distance, allowed) in found {
+            player.movement_violations += 1;
+
+            let v = MovementViolation {
+                player_id: *id,
+                frame: frame_count,
+                state,
+                kind,
+                distance,
+                allowed,
+            };
+            warn!(
+                target: "movement",
+                player = v.player_id,
+                kind = ?v.kind,
+                state = ?v.state,
+                frame = v.frame,
+                moved = v.distance,
+                allowed = v.allowed,
+                total = player.movement_violations,
+                "movement violation"
+            );
+
+            if violations.len() >= MAX_VIOLATION_LOG {
+                violations.pop_front();
+            }
+            violations.push_back(v);
+        }
+
+        player.last_valid_pos = (transform.x, transform.y, transform.z);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::TeamId;
+
+    const DT: f32 = 0.05;
+
+    fn setup(pos: (f32, f32, f32)) -> (EntityStore, EntityId) {
+        let mut entities = EntityStore::new();
+        let id = entities.spawn_player(1, "tester", TeamId::TeamA);
+        let t = entities.transforms.get_mut(&id).unwrap();
+        (t.x, t.y, t.z) = pos;
+        entities.players.get_mut(&id).unwrap().last_valid_pos = pos;
+        (entities, id)
+    }
+
+    /// Move the player to `to` and validate one tick.
+    fn step(entities: &mut EntityStore, id: EntityId, to: (f32, f32, f32)) -> Option<MovementViolation> {
+        let t = entities.transforms.get_mut(&id).unwrap();
+        (t.x, t.y, t.z) = to;
+        let mut log = VecDeque::new();
+        update(entities, &mut log, MovementAuthority::Server, DT, 1);
+        log.pop_back()
+    }
+
+    /// Like `step`, for a client-reported position covering `window` seconds.
+    fn report(
+        entities: &mut EntityStore,
+        id: EntityId,
+        to: (f32, f32, f32),
+        window: f32,
+    ) -> Vec<MovementViolation> {
+        let t = entities.transforms.get_mut(&id).unwrap();
+        (t.x, t.y, t.z) = to;
+        entities.players.get_mut(&id).unwrap().update_window = Some(window);
+        let mut log = VecDeque::new();
+        update(entities, &mut log, MovementAuthority::Client, DT, 1);
+        log.into()
+    }
+
+    /// A client-reported tick without a new update.
+    fn idle(entities: &mut EntityStore) -> Vec<MovementViolation> {
+        let mut log = VecDeque::new();
+        update(entities, &mut log, MovementAuthority::Client, DT, 1);
+        log.into()
+    }
+
+    /// Client-side jump: explicit Euler at 60 Hz, sampled every `every` sim ticks.
+    fn client_jump(every: usize) -> Vec<f32> {
+        let frames_per_tick = 3;
+        let (mut y, mut vy) = (0.0f32, JUMP_FORCE);
+        let mut samples = Vec::new();
+        for tick in 1..=30 {
+            for _ in 0..frames_per_tick {
+                y += vy / 60.0;
+                vy += GRAVITY / 60.0;
+                if y <= 0.0 {
+                    (y, vy) = (0.0, 0.0);
+                }
+            }
+            if tick % every == 0 {
+                samples.push(y);
+            }
+        }
+        samples
+    }
+
+    fn allowed(state: MovementState) -> f32 {
+        state.max_speed() * DT * SPEED_TOLERANCE + DISTANCE_SLACK
+    }
+
+    #[test]
+    fn each_state_has_its_own_speed_limit() {
+        let cases = [
+            (MovementState::Walking, 0.0, false, false),
+            (MovementState::Sprinting, 0.0, true, false),
+            (MovementState::Airborne, 1.0, false, false),
+            (MovementState::InVehicle, 0.0, false, true),
+        ];
+        for (state, y, sprinting, seated) in cases {
+            let (mut entities, id) = setup((0.0, y, 0.0));
+            let player = entities.players.get_mut(&id).unwrap();
+            player.is_sprinting = sprinting;
+            player.vehicle_id = seated.then_some(99);
+            let limit = allowed(state);
+
+            assert!(step(&mut entities, id, (limit * 0.95, y, 0.0)).is_none(), "{:?}", state);
+            assert_eq!(entities.transforms[&id].x, limit * 0.95);
+
+            let v = step(&mut entities, id, (limit * 0.95 + limit * 1.1, y, 0.0)).expect("not caught");
+            assert_eq!(v.kind, ViolationKind::Speed);
+            assert_eq!(v.state, state);
+            assert!((v.allowed - limit).abs() < 1e-5);
+            // Clamped to the allowed distance along the same direction.
+            assert!((entities.transforms[&id].x - limit * 1.95).abs() < 1e-4);
+            assert_eq!(entities.players[&id].movement_violations, 1);
+        }
+    }
+
+    #[test]
+    fn sprint_jump_takeoff_uses_the_grounded_limit() {
+        let (mut entities, id) = setup((0.0, 0.0, 0.0));
+        entities.players.get_mut(&id).unwrap().is_sprinting = true;
+
+        // One tick of sprinting before leaving the ground.
+        let run = MOVE_SPEED * SPRINT_MULTIPLIER * DT;
+        assert!(run > allowed(MovementState::Airborne));
+        assert!(step(&mut entities, id, (run, 0.39, 0.0)).is_none());
+        assert_eq!(entities.players[&id].movement_violations, 0);
+    }
+
+    #[test]
+    fn teleports_snap_back() {
+        let (mut entities, id) = setup((1.0, 0.0, 1.0));
+        entities.players.get_mut(&id).unwrap().velocity = (5.0, 0.0, 5.0);
+
+        let v = step(&mut entities, id, (1.0, 0.0, 1.0 + MAX_TELEPORT_DISTANCE + 1.0)).expect("not caught");
+        assert_eq!(v.kind, ViolationKind::Teleport);
+        let t = entities.transforms[&id];
+        assert_eq!((t.x, t.y, t.z), (1.0, 0.0, 1.0));
+        assert_eq!(entities.players[&id].velocity, (0.0, 0.0, 0.0));
+        assert_eq!(entities.players[&id].last_valid_pos, (1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn dead_players_are_not_checked() {
+        let (mut entities, id) = setup((0.0, 0.0, 0.0));
+        entities.players.get_mut(&id).unwrap().is_dead = true;
+        assert!(step(&mut entities, id, (50.0, 0.0, 0.0)).is_none());
+        assert_eq!(entities.players[&id].last_valid_pos, (50.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn client_jumps_pass_even_with_late_updates() {
+        for every in [1, 2, 3] {
+            let (mut entities, id) = setup((0.0, 0.0, 0.0));
+            for y in client_jump(every) {
+                // Ticks without a new update leave the player where they were.
+                for _ in 1..every {
+                    assert!(idle(&mut entities).is_empty());
+                }
+                let found = report(&mut entities, id, (0.0, y, 0.0), DT * every as f32);
+                assert!(found.is_empty(), "every {}: {:?}", every, found);
+            }
+            assert_eq!(entities.players[&id].movement_violations, 0);
+        }
+    }
+
+    #[test]
+    fn client_reports_cannot_fly() {
+        // Climbing several units in one update is pulled back to a jump's rise.
+        let (mut entities, id) = setup((0.0, 0.0, 0.0));
+        let found = report(&mut entities, id, (0.0, 7.9, 0.0), DT);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, ViolationKind::Vertical);
+        let y = entities.transforms[&id].y;
+        assert!(y > 0.0 && y < JUMP_FORCE * DT * 1.5, "{}", y);
+
+        // Hovering: the same height reported over and over is pulled down
+        // to the ground once the jump would be over.
+        let (mut entities, id) = setup((0.0, 1.0, 0.0));
+        let mut landed = false;
+        for _ in 0..40 {
+            report(&mut entities, id, (0.0, 1.0, 0.0), DT);
+            landed |= movement::is_grounded(&entities.transforms[&id]);
+        }
+        assert!(landed);
+        assert!(entities.players[&id].movement_violations > 0);
+
+        // Below the floor.
+        let (mut entities, id) = setup((0.0, 0.0, 0.0));
+        let found = report(&mut entities, id, (0.0, -2.0, 0.0), DT);
+        assert_eq!(found[0].kind, ViolationKind::BelowGround);
+        assert_eq!(entities.transforms[&id].y, GROUND_HEIGHT);
+    }
+
+    #[test]
+    fn speed_is_measured_over_the_update_window() {
+        // Two ticks' worth of walking in one late update.
+        let distance = MOVE_SPEED * DT * 2.0;
+        let (mut entities, id) = setup((0.0, 0.0, 0.0));
+        assert!(report(&mut entities, id, (distance, 0.0, 0.0), DT * 2.0).is_empty());
+
+        // The same distance in a single tick is too fast.
+        let (mut entities, id) = setup((0.0, 0.0, 0.0));
+        let found = report(&mut entities, id, (distance, 0.0, 0.0), DT);
+        assert_eq!(found[0].kind, ViolationKind::Speed);
+    }
+}
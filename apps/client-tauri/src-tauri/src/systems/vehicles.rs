@@ -0,0 +1,567 @@
+// apps/client-tauri/src-tauri/src/systems/vehicles.rs
+use std::collections::HashMap;
+
+use tracing::{debug, info};
+
+use crate::config::Rules;
+use crate::entity::{EntityId, EntityStore};
+use crate::protocol::{ClientMessage, Transform};
+use crate::sim::{Collider, WorldBounds};
+use crate::systems::combat::{ray_sphere_distance, ATTACKER_CREDIT_WINDOW};
+use crate::systems::movement::GROUND_HEIGHT;
+use crate::vehicle::{
+    hull_center, Vehicle, VehicleRequest, VehicleSpawn, VehicleWeapon, ENTER_RANGE, EXIT_OFFSET,
+    SEAT_HEIGHT,
+};
+
+// Same soldier hit sphere as the hitscan in combat.rs
+const PLAYER_HIT_HEIGHT: f32 = 0.9;
+const PLAYER_HIT_RADIUS: f32 = 1.0;
+
+struct Shot {
+    shooter: EntityId,
+    vehicle_id: EntityId,
+    origin: (f32, f32, f32),
+    dir: (f32, f32, f32),
+    weapon: VehicleWeapon,
+}
+
+/// Vehicle destruction/respawn, enter/exit, driving, seat transforms and gunner weapons.
+#[allow(clippy::too_many_arguments)]
+pub fn update(
+    entities: &mut EntityStore,
+    spawns: &[VehicleSpawn],
+    requests: &mut Vec<(EntityId, VehicleRequest)>,
+    input_map: &HashMap<u32, ClientMessage>,
+    colliders: &[Collider],
+    bounds: &WorldBounds,
+    rules: &Rules,
+    dt: f32,
+) {
+    let mut kills_to_award: Vec<EntityId> = Vec::new();
+
+    // 1. Destruction + respawn timers
+    let mut respawns: Vec<(EntityId, &VehicleSpawn)> = Vec::new();
+    let mut wrecked: Vec<(EntityId, Option<EntityId>)> = Vec::new();
+    for (id, vehicle) in entities.vehicles.iter_mut() {
+        let spawn = spawns.iter().find(|s| s.id == vehicle.spawn_id);
+
+        if vehicle.is_destroyed {
+            vehicle.respawn_timer -= dt;
+            if vehicle.respawn_timer <= 0.0 {
+                if let Some(spawn) = spawn {
+                    respawns.push((*id, spawn));
+                }
+            }
+            continue;
+        }
+
+        let Some(health) = entities.healths.get_mut(id) else { continue };
+        if health.current > 0.0 {
+            continue;
+        }
+
+        info!(target: "vehicles", kind = ?vehicle.kind, vehicle = id, "destroyed");
+        for occupant in vehicle.seats.iter_mut().filter_map(|s| s.take()) {
+            wrecked.push((occupant, vehicle.last_attacker));
+        }
+        health.current = 0.0;
+        vehicle.speed = 0.0;
+        vehicle.is_destroyed = true;
+        vehicle.respawn_timer = spawn.map(|s| s.respawn_time).unwrap_or(0.0);
+    }
+
+    for (id, spawn) in respawns {
+        entities.respawn_vehicle(id, spawn);
+    }
+
+    for (occupant, attacker) in wrecked {
+        if entities.players.get(&occupant).is_none_or(|p| p.is_dead) {
+            continue;
+        }
+        entities.kill_player(occupant, rules.respawn_time);
+        match attacker {
+            Some(attacker) if attacker != occupant => {
+                info!(target: "combat", player = occupant, killer = attacker, "eliminated in vehicle");
+                kills_to_award.push(attacker);
+            }
+            _ => info!(target: "combat", player = occupant, "died in the wreck"),
+        }
+    }
+
+    // 2. Free seats whose occupant died, respawned or disconnected.
+    for vehicle in entities.vehicles.values_mut() {
+        for seat in vehicle.seats.iter_mut() {
+            let still_seated = seat.is_some_and(|pid| {
+                entities
+                    .players
+                    .get(&pid)
+                    .is_some_and(|p| !p.is_dead && p.vehicle_id == Some(vehicle.id))
+            });
+            if !still_seated {
+                *seat = None;
+            }
+        }
+    }
+
+    // 3. Enter / exit requests
+    for (player_id, request) in requests.drain(..) {
+        match request {
+            VehicleRequest::Enter { vehicle_id, seat } => {
+                enter_vehicle(player_id, vehicle_id, seat, entities)
+            }
+            VehicleRequest::Exit => exit_vehicle(player_id, entities),
+        }
+    }
+
+    // 4. Driving
+    if dt > 0.0 {
+        for vehicle in entities.vehicles.values_mut().filter(|v| !v.is_destroyed) {
+            let Some(transform) = entities.transforms.get_mut(&vehicle.id) else { continue };
+            let driver_axes = vehicle.seats[0]
+                .and_then(|pid| entities.players.get(&pid))
+                .and_then(|p| match input_map.get(&p.conn_id) {
+                    Some(ClientMessage::Input { axes, .. }) => Some(axes),
+                    _ => None,
+                });
+            // Same sign convention as on-foot movement: W sends forward = -1.
+            let (throttle, steer) = driver_axes
+                .map(|a| ((-a.forward).clamp(-1.0, 1.0), a.right.clamp(-1.0, 1.0)))
+                .unwrap_or((0.0, 0.0));
+            drive(vehicle, transform, throttle, steer, colliders, bounds, dt);
+        }
+    }
+
+    // 5. Occupants ride along
+    for vehicle in entities.vehicles.values().filter(|v| !v.is_destroyed) {
+        let Some(vt) = entities.transforms.get(&vehicle.id).copied() else { continue };
+        for (seat, occupant) in vehicle.seats.iter().enumerate() {
+            let Some(pid) = occupant else { continue };
+            let Some(p) = entities.players.get_mut(pid) else { continue };
+            let Some(t) = entities.transforms.get_mut(pid) else { continue };
+
+            t.x = vt.x;
+            t.y = vt.y + SEAT_HEIGHT;
+            t.z = vt.z;
+            p.velocity = (0.0, 0.0, 0.0);
+
+            if seat == 0 {
+                t.yaw = vt.yaw;
+                t.pitch = 0.0;
+            } else if let Some(ClientMessage::Input { axes, .. }) = input_map.get(&p.conn_id) {
+                // Gunners and passengers look around freely.
+                t.yaw = axes.yaw;
+                t.pitch = axes.pitch;
+            }
+        }
+    }
+
+    // 6. Gunner weapons
+    let mut shots: Vec<Shot> = Vec::new();
+    if dt > 0.0 {
+        for vehicle in entities.vehicles.values_mut().filter(|v| !v.is_destroyed) {
+            let Some(vt) = entities.transforms.get(&vehicle.id) else { continue };
+            for seat in 0..vehicle.seats.len() {
+                if vehicle.seat_cooldowns[seat] > 0.0 {
+                    vehicle.seat_cooldowns[seat] -= dt;
+                }
+                let Some(weapon) = vehicle.kind.seat_weapon(seat) else { continue };
+                let Some(pid) = vehicle.seats[seat] else { continue };
+                let Some(p) = entities.players.get(&pid) else { continue };
+                let Some(ClientMessage::Input { axes, .. }) = input_map.get(&p.conn_id) else {
+                    continue;
+                };
+                if !axes.shoot || vehicle.seat_cooldowns[seat] > 0.0 {
+                    continue;
+                }
+                vehicle.seat_cooldowns[seat] = weapon.fire_rate;
+
+                shots.push(Shot {
+                    shooter: pid,
+                    vehicle_id: vehicle.id,
+                    origin: hull_center(vt),
+                    dir: (
+                        axes.yaw.sin() * axes.pitch.cos(),
+                        axes.pitch.sin(),
+                        axes.yaw.cos() * axes.pitch.cos(),
+                    ),
+                    weapon,
+                });
+            }
+        }
+    }
+
+    for shot in shots {
+        resolve_shot(&shot, entities, rules, &mut kills_to_award);
+    }
+
+    for attacker in kills_to_award {
+        entities.award_kill(attacker);
+    }
+}
+
+fn enter_vehicle(
+    player_id: EntityId,
+    vehicle_id: EntityId,
+    requested_seat: Option<u8>,
+    entities: &mut EntityStore,
+) {
+    let Some(player) = entities.players.get_mut(&player_id) else { return };
+    if player.is_dead || player.vehicle_id.is_some() {
+        return;
+    }
+    let Some(vehicle) = entities.vehicles.get_mut(&vehicle_id) else { return };
+    if vehicle.is_destroyed {
+        return;
+    }
+    let Some(vt) = entities.transforms.get(&vehicle_id).copied() else { return };
+    let Some(t) = entities.transforms.get_mut(&player_id) else { return };
+
+    let dx = t.x - vt.x;
+    let dz = t.z - vt.z;
+    if (dx * dx + dz * dz).sqrt() > ENTER_RANGE {
+        debug!(target: "vehicles", player = player_id, vehicle = vehicle_id, "too far to enter");
+        return;
+    }
+
+    let seat = match requested_seat {
+        Some(s) => Some(s as usize).filter(|s| vehicle.seats.get(*s).is_some_and(|o| o.is_none())),
+        None => vehicle.seats.iter().position(|o| o.is_none()),
+    };
+    let Some(seat) = seat else {
+        debug!(target: "vehicles", player = player_id, vehicle = vehicle_id, "no free seat");
+        return;
+    };
+
+    vehicle.seats[seat] = Some(player_id);
+    player.vehicle_id = Some(vehicle_id);
+    player.velocity = (0.0, 0.0, 0.0);
+    t.x = vt.x;
+    t.y = vt.y + SEAT_HEIGHT;
+    t.z = vt.z;
+    // Getting in is a legitimate jump in position.
+    player.last_valid_pos = (t.x, t.y, t.z);
+    info!(target: "vehicles", player = player_id, kind = ?vehicle.kind, vehicle = vehicle_id, seat, "entered");
+}
+
+fn exit_vehicle(player_id: EntityId, entities: &mut EntityStore) {
+    let Some(player) = entities.players.get_mut(&player_id) else { return };
+    let Some(vehicle_id) = player.vehicle_id.take() else { return };
+    let Some(vehicle) = entities.vehicles.get_mut(&vehicle_id) else { return };
+    let Some(vt) = entities.transforms.get(&vehicle_id).copied() else { return };
+    let Some(t) = entities.transforms.get_mut(&player_id) else { return };
+
+    if let Some(seat) = vehicle.seat_of(player_id) {
+        vehicle.seats[seat] = None;
+    }
+
+    // Step out to the vehicle's right.
+    t.x = vt.x + vt.yaw.cos() * EXIT_OFFSET;
+    t.y = GROUND_HEIGHT;
+    t.z = vt.z - vt.yaw.sin() * EXIT_OFFSET;
+    player.velocity = (0.0, 0.0, 0.0);
+    player.last_valid_pos = (t.x, t.y, t.z);
+    info!(target: "vehicles", player = player_id, vehicle = vehicle_id, "exited");
+}
+
+/// Simple arcade physics: accelerate along the heading, steer, stop at walls.
+fn drive(
+    vehicle: &mut Vehicle,
+    transform: &mut Transform,
+    throttle: f32,
+    steer: f32,
+    colliders: &[Collider],
+    bounds: &WorldBounds,
+    dt: f32,
+) {
+    let kind = vehicle.kind;
+
+    if throttle != 0.0 {
+        vehicle.speed += throttle * kind.acceleration() * dt;
+    } else {
+        // Coast to a stop.
+        let brake = kind.acceleration() * dt;
+        vehicle.speed = if vehicle.speed.abs() <= brake {
+            0.0
+        } else {
+            vehicle.speed - brake * vehicle.speed.signum()
+        };
+    }
+    vehicle.speed = vehicle.speed.clamp(-kind.max_reverse_speed(), kind.max_speed());
+
+    let steer_factor = if kind.can_pivot() {
+        1.0
+    } else {
+        (vehicle.speed / kind.max_speed()).clamp(-1.0, 1.0)
+    };
+    transform.yaw += steer * kind.turn_rate() * steer_factor * dt;
+
+    let yaw = transform.yaw;
+    let nx = transform.x + yaw.sin() * vehicle.speed * dt;
+    let nz = transform.z + yaw.cos() * vehicle.speed * dt;
+    let hull_y = transform.y + SEAT_HEIGHT;
+
+    let blocked = nx < bounds.min_x
+        || nx > bounds.max_x
+        || nz < bounds.min_z
+        || nz > bounds.max_z
+        || colliders.iter().any(|c| {
+            nx >= c.min_x && nx <= c.max_x && hull_y >= c.min_y && hull_y <= c.max_y && nz >= c.min_z && nz <= c.max_z
+        });
+
+    if blocked {
+        vehicle.speed = 0.0;
+    } else {
+        transform.x = nx;
+        transform.z = nz;
+    }
+    transform.y = GROUND_HEIGHT;
+}
+
+/// Hitscan from a gunner seat against soldiers and other vehicles.
+fn resolve_shot(shot: &Shot, entities: &mut EntityStore, rules: &Rules, kills_to_award: &mut Vec<EntityId>) {
+    let mut best_dist = shot.weapon.range;
+    let mut hit_player = None;
+    let mut hit_vehicle = None;
+
+    for (pid, p) in entities.players.iter() {
+        if *pid == shot.shooter || p.is_dead || p.vehicle_id.is_some() {
+            continue;
+        }
+        let Some(t) = entities.transforms.get(pid) else { continue };
+        let center = (t.x, t.y + PLAYER_HIT_HEIGHT, t.z);
+        if let Some(d) = ray_sphere_distance(shot.origin, shot.dir, center, PLAYER_HIT_RADIUS) {
+            if d < best_dist {
+                best_dist = d;
+                hit_player = Some(*pid);
+            }
+        }
+    }
+
+    for v in entities.vehicles.values() {
+        if v.id == shot.vehicle_id || v.is_destroyed {
+            continue;
+        }
+        let Some(t) = entities.transforms.get(&v.id) else { continue };
+        if let Some(d) = ray_sphere_distance(shot.origin, shot.dir, hull_center(t), v.kind.hit_radius()) {
+            if d < best_dist {
+                best_dist = d;
+                hit_player = None;
+                hit_vehicle = Some(v.id);
+            }
+        }
+    }
+
+    if let Some(pid) = hit_player {
+        if !rules.friendly_fire && entities.teammates(shot.shooter, pid) {
+            return;
+        }
+        let Some(victim) = entities.players.get_mut(&pid) else { return };
+        victim.last_attacker = Some(shot.shooter);
+        victim.last_attacker_timer = ATTACKER_CREDIT_WINDOW;
+        let Some(hp) = entities.damage(pid, shot.weapon.damage_players) else { return };
+        debug!(target: "combat", shooter = shot.shooter, victim = pid, vehicle = shot.vehicle_id, hp, "hit from vehicle");
+        if hp <= 0.0 {
+            entities.kill_player(pid, rules.respawn_time);
+            info!(target: "combat", player = pid, killer = shot.shooter, "eliminated");
+            kills_to_award.push(shot.shooter);
+        }
+    } else if let Some(vid) = hit_vehicle {
+        if !rules.friendly_fire && entities.friendly_vehicle(shot.shooter, vid) {
+            return;
+        }
+        entities.damage_vehicle(vid, shot.weapon.damage_vehicles, shot.shooter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{ClientAxes, TeamId, VehicleKind};
+    use crate::sim::SimState;
+
+    const DT: f32 = 0.05;
+
+    /// Join and stand on the ground at `x, z`.
+    fn join_at(sim: &mut SimState, x: f32, z: f32) -> (u32, EntityId) {
+        let conn = sim.allocate_connection_id();
+        let eid = sim.handle_join(conn, "tester", None).unwrap();
+        let t = sim.entities.transforms.get_mut(&eid).unwrap();
+        (t.x, t.y, t.z) = (x, 0.0, z);
+        sim.entities.players.get_mut(&eid).unwrap().last_valid_pos = (x, 0.0, z);
+        (conn, eid)
+    }
+
+    fn vehicle(sim: &SimState, spawn_id: u32) -> EntityId {
+        *sim.entities.vehicles.keys().find(|id| sim.entities.vehicles[id].spawn_id == spawn_id).unwrap()
+    }
+
+    fn input(forward: f32, shoot: bool, yaw: f32) -> ClientMessage {
+        let axes = ClientAxes { forward, right: 0.0, jump: false, shoot, reload: false, sprint: false, yaw, pitch: 0.0 };
+        ClientMessage::Input { tick: 1, axes }
+    }
+
+    fn enter(sim: &mut SimState, conn: u32, vehicle_id: EntityId, seat: Option<u8>) {
+        sim.queue_vehicle_request(conn, VehicleRequest::Enter { vehicle_id, seat });
+        sim.update(DT, &HashMap::new());
+    }
+
+    #[test]
+    fn seats_fill_in_order_and_free_up_on_exit() {
+        let mut sim = SimState::new();
+        let jeep = vehicle(&sim, 1);
+        let (c1, driver) = join_at(&mut sim, 25.0, 22.0);
+        let (c2, gunner) = join_at(&mut sim, 24.0, 20.0);
+        let (c3, far) = join_at(&mut sim, 0.0, 0.0);
+
+        enter(&mut sim, c1, jeep, None);
+        assert_eq!(sim.entities.vehicles[&jeep].seats[0], Some(driver));
+        assert_eq!(sim.entities.players[&driver].vehicle_id, Some(jeep));
+
+        // Taken seat, then the next free one.
+        enter(&mut sim, c2, jeep, Some(0));
+        assert_eq!(sim.entities.players[&gunner].vehicle_id, None);
+        enter(&mut sim, c2, jeep, None);
+        assert_eq!(sim.entities.vehicles[&jeep].seats[1], Some(gunner));
+        let t = sim.entities.transforms[&gunner];
+        assert_eq!((t.x, t.y, t.z), (25.0, SEAT_HEIGHT, 20.0));
+
+        enter(&mut sim, c3, jeep, None);
+        assert_eq!(sim.entities.players[&far].vehicle_id, None);
+
+        sim.queue_vehicle_request(c1, VehicleRequest::Exit);
+        sim.update(DT, &HashMap::new());
+        assert_eq!(sim.entities.vehicles[&jeep].seats[0], None);
+        assert_eq!(sim.entities.players[&driver].vehicle_id, None);
+        let t = sim.entities.transforms[&driver];
+        assert_eq!((t.x, t.y, t.z), (25.0 + EXIT_OFFSET, GROUND_HEIGHT, 20.0));
+        assert_eq!(sim.entities.players[&driver].movement_violations, 0);
+
+        // Leaving the server frees the seat too.
+        sim.handle_disconnect(c2, crate::session::DisconnectReason::ClientClosed);
+        sim.update(DT, &HashMap::new());
+        assert_eq!(sim.entities.vehicles[&jeep].seats[1], None);
+    }
+
+    #[test]
+    fn drivers_steer_and_passengers_ride_along() {
+        let mut sim = SimState::new();
+        let jeep = vehicle(&sim, 1);
+        let (c1, driver) = join_at(&mut sim, 25.0, 20.0);
+        enter(&mut sim, c1, jeep, None);
+
+        let inputs = HashMap::from([(c1, input(-1.0, false, 0.0))]);
+        for _ in 0..20 {
+            sim.update(DT, &inputs);
+        }
+        let vt = sim.entities.transforms[&jeep];
+        assert!(vt.z > 25.0);
+        assert_eq!(sim.entities.transforms[&driver].z, vt.z);
+        assert_eq!(sim.entities.players[&driver].movement_violations, 0);
+
+        // Letting go coasts to a stop.
+        for _ in 0..60 {
+            sim.update(DT, &HashMap::new());
+        }
+        assert_eq!(sim.entities.vehicles[&jeep].speed, 0.0);
+    }
+
+    #[test]
+    fn destroyed_vehicles_kill_occupants_and_respawn() {
+        let mut sim = SimState::new();
+        let jeep = vehicle(&sim, 1);
+        let (c1, driver) = join_at(&mut sim, 25.0, 20.0);
+        let (_, attacker) = join_at(&mut sim, 0.0, 0.0);
+        enter(&mut sim, c1, jeep, None);
+
+        sim.entities.damage_vehicle(jeep, 1000.0, attacker);
+        sim.update(DT, &HashMap::new());
+        assert!(sim.entities.vehicles[&jeep].is_destroyed);
+        assert!(sim.entities.players[&driver].is_dead);
+        assert_eq!(sim.entities.vehicles[&jeep].seats[0], None);
+        assert_eq!(sim.entities.scores[&attacker].kills, 1);
+
+        // Wrecks can't be entered.
+        let (c3, late) = join_at(&mut sim, 25.0, 20.0);
+        enter(&mut sim, c3, jeep, None);
+        assert_eq!(sim.entities.players[&late].vehicle_id, None);
+
+        let respawn = sim.vehicle_spawns[0].respawn_time;
+        for _ in 0..((respawn / DT) as usize + 2) {
+            sim.update(DT, &HashMap::new());
+        }
+        let v = &sim.entities.vehicles[&jeep];
+        assert!(!v.is_destroyed);
+        assert_eq!(sim.entities.healths[&jeep].current, v.kind.max_health());
+        let t = sim.entities.transforms[&jeep];
+        assert_eq!((t.x, t.z), (25.0, 20.0));
+    }
+
+    #[test]
+    fn gunners_hit_soldiers_and_vehicles() {
+        let mut sim = SimState::new();
+        sim.rules.friendly_fire = false;
+        let jeep = vehicle(&sim, 1);
+        let tank = vehicle(&sim, 2);
+        // Teams alternate: gunner A, enemy B, friend A, tank gunner B.
+        let (c1, gunner) = join_at(&mut sim, 25.0, 20.0);
+        let (_, enemy) = join_at(&mut sim, 25.0, 35.0);
+        let (_, friend) = join_at(&mut sim, 25.0, 5.0);
+        let (c4, tank_gunner) = join_at(&mut sim, -25.0, -20.0);
+        assert_eq!(sim.entities.teams[&friend], TeamId::TeamA);
+        enter(&mut sim, c1, jeep, Some(1));
+        enter(&mut sim, c4, tank, Some(1));
+
+        // One shot per fire interval, straight down +z at the enemy.
+        let inputs = HashMap::from([(c1, input(0.0, true, 0.0))]);
+        sim.update(DT, &inputs);
+        let weapon = VehicleKind::Jeep.seat_weapon(1).unwrap();
+        assert_eq!(sim.entities.healths[&enemy].current, 100.0 - weapon.damage_players);
+        assert_eq!(sim.entities.players[&enemy].last_attacker, Some(gunner));
+
+        // Turned around towards a teammate: no damage without friendly fire.
+        let inputs = HashMap::from([(c1, input(0.0, true, std::f32::consts::PI))]);
+        for _ in 0..4 {
+            sim.update(DT, &inputs);
+        }
+        assert_eq!(sim.entities.healths[&friend].current, 100.0);
+
+        // The tank's main gun, aimed at the jeep.
+        let yaw = (50.0f32).atan2(40.0);
+        let inputs = HashMap::from([(c4, input(0.0, true, yaw))]);
+        sim.update(DT, &inputs);
+        let shell = VehicleKind::Tank.seat_weapon(1).unwrap();
+        assert_eq!(sim.entities.healths[&jeep].current, VehicleKind::Jeep.max_health() - shell.damage_vehicles);
+        assert_eq!(sim.entities.vehicles[&jeep].last_attacker, Some(tank_gunner));
+    }
+
+    #[test]
+    fn rifles_spare_friendly_vehicles_without_friendly_fire() {
+        let mut sim = SimState::new();
+        sim.rules.friendly_fire = false;
+        let jeep = vehicle(&sim, 1);
+        // Teams alternate: driver A, enemy B, friend A.
+        let (c1, _) = join_at(&mut sim, 25.0, 22.0);
+        let (c2, _) = join_at(&mut sim, 24.0, 5.0);
+        let (c3, _) = join_at(&mut sim, 26.0, 5.0);
+        enter(&mut sim, c1, jeep, Some(0));
+
+        let full = VehicleKind::Jeep.max_health();
+        let scratch = sim.rules.damage_per_hit * VehicleKind::Jeep.small_arms_factor();
+        let shoot = |sim: &mut SimState, conn: u32, dx: f32| {
+            sim.update(DT, &HashMap::from([(conn, input(0.0, true, dx.atan2(15.0)))]));
+            for _ in 0..4 {
+                sim.update(DT, &HashMap::new());
+            }
+        };
+
+        shoot(&mut sim, c3, -1.0);
+        assert_eq!(sim.entities.healths[&jeep].current, full);
+        shoot(&mut sim, c2, 1.0);
+        assert_eq!(sim.entities.healths[&jeep].current, full - scratch);
+
+        sim.rules.friendly_fire = true;
+        shoot(&mut sim, c3, -1.0);
+        assert_eq!(sim.entities.healths[&jeep].current, full - 2.0 * scratch);
+    }
+}
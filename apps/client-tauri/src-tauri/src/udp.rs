@@ -0,0 +1,414 @@
+// apps/client-tauri/src-tauri/src/udp.rs
+
+// Unreliable channel for inputs and snapshots. The WebSocket stays the
+// reliable one: handshake, vehicle/spawn requests and close reasons go over
+// it, and the first envelope carries a UDP bind token. A client that sends
+// `Bind` with that token gets snapshots at that address and may send inputs
+// from it. Clients that never bind, or go quiet on UDP, get snapshots over
+// the WebSocket as before.
+//
+// Packet layout: [kind u8][seq u32 BE][ack u32 BE][payload]
+// Each side numbers its own packets and acks the newest seq it received.
+// Inputs and snapshots carry full state, so a lost packet is superseded by
+// the next one and late ones are dropped.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tracing::{debug, error, info, warn};
+
+use crate::events::Traffic;
+use crate::outbox::Outbox;
+use crate::protocol::ClientMessage;
+use crate::session::new_token;
+
+pub const HEADER_LEN: usize = 9;
+// Snapshots bigger than this go over the WebSocket instead.
+pub const MAX_DATAGRAM: usize = 16 * 1024;
+// A bound client that sends nothing on UDP for this long gets snapshots over
+// the WebSocket again. Clients re-send `Bind` while they have no input to send.
+const UDP_SILENCE: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketKind {
+    // Client -> server, payload is the bind token.
+    Bind = 1,
+    // Server -> client, empty payload.
+    BindAck = 2,
+    // Client -> server, payload is a msgpack `ClientMessage`.
+    Input = 3,
+    // Server -> client, payload is a msgpack `ServerEnvelope`.
+    Snapshot = 4,
+}
+
+impl PacketKind {
+    fn from_u8(b: u8) -> Option<Self> {
+        match b {
+            1 => Some(PacketKind::Bind),
+            2 => Some(PacketKind::BindAck),
+            3 => Some(PacketKind::Input),
+            4 => Some(PacketKind::Snapshot),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Packet<'a> {
+    pub kind: PacketKind,
+    pub seq: u32,
+    pub ack: u32,
+    pub payload: &'a [u8],
+}
+
+pub fn encode_packet(kind: PacketKind, seq: u32, ack: u32, payload: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(HEADER_LEN + payload.len());
+    buf.push(kind as u8);
+    buf.extend_from_slice(&seq.to_be_bytes());
+    buf.extend_from_slice(&ack.to_be_bytes());
+    buf.extend_from_slice(payload);
+    buf
+}
+
+pub fn decode_packet(buf: &[u8]) -> Option<Packet<'_>> {
+    if buf.len() < HEADER_LEN {
+        return None;
+    }
+    Some(Packet {
+        kind: PacketKind::from_u8(buf[0])?,
+        seq: u32::from_be_bytes(buf[1..5].try_into().ok()?),
+        ack: u32::from_be_bytes(buf[5..9].try_into().ok()?),
+        payload: &buf[HEADER_LEN..],
+    })
+}
+
+/// True if `a` comes after `b`, allowing for wraparound.
+pub fn seq_newer(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) > 0
+}
+
+/// UDP state for one WebSocket connection.
+struct Binding {
+    token: String,
+    peer: Option<SocketAddr>,
+    // Decoded inputs go to the connection task, same as WebSocket inputs.
+    inputs: UnboundedSender<ClientMessage>,
+    // Snapshots go here while the client isn't reachable over UDP.
+    fallback: Outbox,
+    last_recv: Instant,
+    // Newest input seq; anything at or before it is late or duplicated.
+    recv_seq: Option<u32>,
+    send_seq: u32,
+    // Newest snapshot seq the client reports having.
+    acked: u32,
+    received: u64,
+    lost: u64,
+    late: u64,
+}
+
+#[derive(Default)]
+struct Bindings {
+    by_conn: HashMap<u32, Binding>,
+    by_token: HashMap<String, u32>,
+    by_peer: HashMap<SocketAddr, u32>,
+}
+
+enum Command {
+    Register(u32, Binding),
+    Unregister(u32),
+    Snapshot(u32, Vec<u8>),
+}
+
+/// Handle to the UDP task. Cheap to clone; the bindings live in the task,
+/// which connections reach over a channel.
+#[derive(Clone)]
+pub struct UdpHub {
+    commands: UnboundedSender<Command>,
+    port: Option<u16>,
+}
+
+/// The UDP socket and every binding. Owned by the task running `run`.
+pub struct UdpTask {
+    socket: UdpSocket,
+    commands: UnboundedReceiver<Command>,
+    bindings: Bindings,
+    traffic: Arc<Traffic>,
+}
+
+impl UdpHub {
+    /// Bind the socket. The returned task must be spawned for the hub to do anything.
+    pub async fn bind(addr: &str, traffic: Arc<Traffic>) -> Result<(UdpHub, UdpTask), String> {
+        let socket = UdpSocket::bind(addr).await.map_err(|e| e.to_string())?;
+        let (commands, rx) = unbounded_channel();
+        let hub = UdpHub {
+            commands,
+            port: socket.local_addr().ok().map(|a| a.port()),
+        };
+        let task = UdpTask {
+            socket,
+            commands: rx,
+            bindings: Bindings::default(),
+            traffic,
+        };
+        Ok((hub, task))
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    /// Issue a bind token for a connection. Inputs received over UDP come out
+    /// of the returned receiver; snapshots the client can't get over UDP go
+    /// to `fallback`.
+    pub fn register(&self, conn_id: u32, fallback: Outbox) -> (String, UnboundedReceiver<ClientMessage>) {
+        let token = new_token();
+        let (tx, rx) = unbounded_channel();
+        let binding = Binding {
+            token: token.clone(),
+            peer: None,
+            inputs: tx,
+            fallback,
+            last_recv: Instant::now(),
+            recv_seq: None,
+            send_seq: 0,
+            acked: 0,
+            received: 0,
+            lost: 0,
+            late: 0,
+        };
+        let _ = self.commands.send(Command::Register(conn_id, binding));
+        (token, rx)
+    }
+
+    pub fn unregister(&self, conn_id: u32) {
+        let _ = self.commands.send(Command::Unregister(conn_id));
+    }
+
+    /// Send an encoded `ServerEnvelope` over UDP, or over the connection's
+    /// fallback outbox if the client isn't (or is no longer) reachable there.
+    pub fn send_snapshot(&self, conn_id: u32, envelope: Vec<u8>) {
+        let _ = self.commands.send(Command::Snapshot(conn_id, envelope));
+    }
+}
+
+impl UdpTask {
+    /// Receive loop. Runs until the socket fails or every hub handle is gone.
+    pub async fn run(mut self) {
+        let mut buf = vec![0u8; MAX_DATAGRAM];
+        loop {
+            tokio::select! {
+                received = self.socket.recv_from(&mut buf) => {
+                    let (len, from) = match received {
+                        Ok(r) => r,
+                        // ICMP port unreachable from a client that went away shows up
+                        // here on some platforms; it says nothing about our socket.
+                        Err(e) if e.kind() == std::io::ErrorKind::ConnectionReset => continue,
+                        Err(e) => {
+                            error!(target: "net", error = %e, "UDP socket error");
+                            return;
+                        }
+                    };
+                    self.traffic.received(len);
+                    let Some(packet) = decode_packet(&buf[..len]) else { continue };
+                    match packet.kind {
+                        PacketKind::Bind => self.handle_bind(&packet, from),
+                        PacketKind::Input => self.handle_input(&packet, from),
+                        // Server-to-client kinds.
+                        PacketKind::BindAck | PacketKind::Snapshot => {}
+                    }
+                }
+                command = self.commands.recv() => match command {
+                    Some(Command::Register(conn_id, binding)) => self.register(conn_id, binding),
+                    Some(Command::Unregister(conn_id)) => self.unregister(conn_id),
+                    Some(Command::Snapshot(conn_id, envelope)) => self.send_snapshot(conn_id, envelope),
+                    None => return,
+                },
+            }
+        }
+    }
+
+    fn register(&mut self, conn_id: u32, binding: Binding) {
+        let b = &mut self.bindings;
+        b.by_token.insert(binding.token.clone(), conn_id);
+        b.by_conn.insert(conn_id, binding);
+    }
+
+    fn unregister(&mut self, conn_id: u32) {
+        let b = &mut self.bindings;
+        let Some(binding) = b.by_conn.remove(&conn_id) else { return };
+        b.by_token.remove(&binding.token);
+        if let Some(peer) = binding.peer {
+            b.by_peer.remove(&peer);
+            info!(
+                target: "net",
+                %peer,
+                player = conn_id,
+                inputs = binding.received,
+                lost = binding.lost,
+                late = binding.late,
+                acked = binding.acked,
+                sent = binding.send_seq,
+                "UDP stats"
+            );
+        }
+    }
+
+    fn send_snapshot(&mut self, conn_id: u32, envelope: Vec<u8>) {
+        let Some(binding) = self.bindings.by_conn.get_mut(&conn_id) else { return };
+        let peer = match binding.peer {
+            Some(peer) if envelope.len() + HEADER_LEN <= MAX_DATAGRAM && binding.last_recv.elapsed() < UDP_SILENCE => peer,
+            _ => return binding.fallback.push_snapshot(envelope),
+        };
+
+        binding.send_seq = binding.send_seq.wrapping_add(1);
+        let packet = encode_packet(
+            PacketKind::Snapshot,
+            binding.send_seq,
+            binding.recv_seq.unwrap_or(0),
+            &envelope,
+        );
+        // A full socket buffer is just another lost packet.
+        if self.socket.try_send_to(&packet, peer).is_ok() {
+            self.traffic.sent(packet.len());
+        }
+    }
+
+    fn handle_bind(&mut self, packet: &Packet, from: SocketAddr) {
+        let Ok(token) = std::str::from_utf8(packet.payload) else { return };
+        let b = &mut self.bindings;
+        let Some(&conn_id) = b.by_token.get(token) else { return };
+        let Some(binding) = b.by_conn.get_mut(&conn_id) else { return };
+
+        let old = binding.peer.replace(from);
+        binding.last_recv = Instant::now();
+        if old != Some(from) {
+            if let Some(old) = old {
+                b.by_peer.remove(&old);
+            }
+            b.by_peer.insert(from, conn_id);
+            info!(target: "net", player = conn_id, peer = %from, "bound UDP");
+        }
+        let _ = self.socket.try_send_to(&encode_packet(PacketKind::BindAck, 0, packet.seq, &[]), from);
+    }
+
+    fn handle_input(&mut self, packet: &Packet, from: SocketAddr) {
+        let b = &mut self.bindings;
+        let Some(&conn_id) = b.by_peer.get(&from) else { return };
+        let Some(binding) = b.by_conn.get_mut(&conn_id) else { return };
+
+        binding.last_recv = Instant::now();
+        if seq_newer(packet.ack, binding.acked) {
+            binding.acked = packet.ack;
+        }
+        match binding.recv_seq {
+            Some(last) if !seq_newer(packet.seq, last) => {
+                binding.late += 1;
+                return;
+            }
+            Some(last) => binding.lost += u64::from(packet.seq.wrapping_sub(last) - 1),
+            None => {}
+        }
+        binding.recv_seq = Some(packet.seq);
+        binding.received += 1;
+
+        match rmp_serde::from_slice::<ClientMessage>(packet.payload) {
+            Ok(msg) if allowed_over_udp(&msg) => {
+                let _ = binding.inputs.send(msg);
+            }
+            Ok(other) => debug!(target: "net", player = conn_id, message = ?other, "ignoring message over UDP"),
+            Err(e) => warn!(target: "net", player = conn_id, error = ?e, "bad UDP input"),
+        }
+    }
+}
+
+/// Only state that the next packet supersedes may come over UDP. Events
+/// (shots, vehicle requests) need the reliable channel: a lost datagram
+/// would silently drop them.
+fn allowed_over_udp(msg: &ClientMessage) -> bool {
+    matches!(
+        msg,
+        ClientMessage::Input { .. } | ClientMessage::StateUpdate { .. } | ClientMessage::SnapshotAck { .. }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packet_round_trip() {
+        let bytes = encode_packet(PacketKind::Input, 7, u32::MAX, b"abc");
+        assert_eq!(bytes.len(), HEADER_LEN + 3);
+        let packet = decode_packet(&bytes).unwrap();
+        assert_eq!(
+            packet,
+            Packet { kind: PacketKind::Input, seq: 7, ack: u32::MAX, payload: b"abc" }
+        );
+        assert!(decode_packet(&bytes[..HEADER_LEN - 1]).is_none());
+        assert!(decode_packet(&[9, 0, 0, 0, 0, 0, 0, 0, 0]).is_none());
+    }
+
+    #[test]
+    fn sequence_numbers_wrap() {
+        assert!(seq_newer(2, 1));
+        assert!(!seq_newer(1, 2));
+        assert!(!seq_newer(5, 5));
+        assert!(seq_newer(0, u32::MAX));
+        assert!(seq_newer(3, u32::MAX - 3));
+        assert!(!seq_newer(u32::MAX, 0));
+    }
+
+    #[test]
+    fn events_stay_on_the_websocket() {
+        assert!(allowed_over_udp(&ClientMessage::SnapshotAck { tick: 3 }));
+        assert!(!allowed_over_udp(&ClientMessage::ExitVehicle));
+        let fire = ClientMessage::Fire {
+            tick: 1,
+            origin: crate::protocol::Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+            direction: crate::protocol::Vec3 { x: 0.0, y: 0.0, z: 1.0 },
+            weapon_id: 4,
+        };
+        assert!(!allowed_over_udp(&fire));
+    }
+    #[tokio::test]
+    async fn hub_moves_a_connection_to_udp_once_bound() {
+        let (hub, task) = UdpHub::bind("127.0.0.1:0", Arc::new(Traffic::default())).await.unwrap();
+        tokio::spawn(task.run());
+        let server: SocketAddr = ([127, 0, 0, 1], hub.port().unwrap()).into();
+        let (outbox, writer, _) = crate::outbox::channel(4, Duration::from_secs(5));
+        let (token, mut inputs) = hub.register(1, outbox.clone());
+
+        // Not bound yet: the snapshot goes to the WebSocket outbox.
+        hub.send_snapshot(1, vec![1]);
+
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut buf = vec![0u8; MAX_DATAGRAM];
+        client.send_to(&encode_packet(PacketKind::Bind, 1, 0, token.as_bytes()), server).await.unwrap();
+        let len = client.recv(&mut buf).await.unwrap();
+        assert_eq!(decode_packet(&buf[..len]).unwrap().kind, PacketKind::BindAck);
+
+        hub.send_snapshot(1, vec![2]);
+        let len = client.recv(&mut buf).await.unwrap();
+        let packet = decode_packet(&buf[..len]).unwrap();
+        assert_eq!((packet.kind, packet.payload), (PacketKind::Snapshot, &[2u8][..]));
+
+        let ack = rmp_serde::to_vec_named(&ClientMessage::SnapshotAck { tick: 9 }).unwrap();
+        client.send_to(&encode_packet(PacketKind::Input, 2, 1, &ack), server).await.unwrap();
+        let received = tokio::time::timeout(Duration::from_secs(1), inputs.recv()).await.unwrap();
+        assert!(matches!(received, Some(ClientMessage::SnapshotAck { tick: 9 })));
+
+        outbox.close();
+        let (tx, mut out) = unbounded_channel();
+        let sink = futures_util::sink::unfold(tx, |tx, msg| async move {
+            let _ = tx.send(msg);
+            Ok::<_, ()>(tx)
+        });
+        writer.run(Box::pin(sink), |_| {}).await;
+        assert_eq!(out.try_recv(), Ok(tokio_tungstenite::tungstenite::Message::Binary(vec![1])));
+        assert!(out.try_recv().is_err());
+    }
+}
@@ -0,0 +1,312 @@
+// apps/client-tauri/src-tauri/src/validate.rs
+use std::collections::VecDeque;
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+use std::time::{Duration, Instant};
+
+use crate::protocol::{ClientAxes, ClientMessage, Vec3};
+
+// Coordinates beyond this are garbage, whatever the map.
+const MAX_COORD: f32 = 100_000.0;
+// Angles are wrapped, but values this large have lost all precision.
+const MAX_ANGLE: f32 = 1_000.0;
+// Client ticks may arrive this far out of order (UDP reordering).
+const TICK_REORDER_WINDOW: u32 = 64;
+// Client ticks count rendered frames; this allows for high refresh rate
+// displays, plus slack for bursts.
+const MAX_TICK_RATE: f32 = 500.0;
+const TICK_SLACK: u32 = 64;
+
+/// Rate limit and strike policy for client messages.
+#[derive(Debug, Clone, Copy)]
+pub struct GuardConfig {
+    // Sustained messages per second. Inputs are sent every rendered frame.
+    pub messages_per_sec: f32,
+    // Messages allowed in a burst above the sustained rate.
+    pub burst: f32,
+    // Kick after this many violations inside `violation_window`.
+    pub max_violations: usize,
+    pub violation_window: Duration,
+}
+
+impl Default for GuardConfig {
+    fn default() -> Self {
+        Self {
+            messages_per_sec: 500.0,
+            burst: 250.0,
+            max_violations: 20,
+            violation_window: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Classic token bucket: `rate` tokens a second, holding at most `capacity`.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    rate: f32,
+    capacity: f32,
+    tokens: f32,
+    last: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate: f32, capacity: f32, now: Instant) -> Self {
+        Self { rate, capacity, tokens: capacity, last: now }
+    }
+
+    /// Take one token if there is one.
+    pub fn take(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f32();
+        self.last = now;
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// What to do with one message from a client.
+#[derive(Debug)]
+pub enum Verdict {
+    Accept(ClientMessage),
+    // Rate limited or invalid; the reason is for the log.
+    Reject(String),
+    // Too many violations; the reason goes in the close frame.
+    Kick(String),
+}
+
+/// Per-connection message checks: rate limit, field validation and a
+/// running count of violations.
+pub struct InputGuard {
+    config: GuardConfig,
+    bucket: TokenBucket,
+    // Newest client tick seen and when, to bound how fast ticks advance.
+    last_tick: Option<(u32, Instant)>,
+    violations: VecDeque<Instant>,
+}
+
+impl InputGuard {
+    pub fn new(config: GuardConfig, now: Instant) -> Self {
+        Self {
+            config,
+            bucket: TokenBucket::new(config.messages_per_sec, config.burst, now),
+            last_tick: None,
+            violations: VecDeque::new(),
+        }
+    }
+
+    /// Check one decoded message. `sent_tick` is the newest snapshot sent to
+    /// the client, which no ack can be ahead of.
+    pub fn check(&mut self, msg: ClientMessage, sent_tick: u64, now: Instant) -> Verdict {
+        if !self.bucket.take(now) {
+            return self.strike("rate limited".to_string(), now);
+        }
+        match self.validate(msg, sent_tick, now) {
+            Ok(msg) => Verdict::Accept(msg),
+            Err(why) => self.strike(why, now),
+        }
+    }
+
+    /// Count a violation found outside `check`, e.g. an undecodable message.
+    pub fn strike(&mut self, why: String, now: Instant) -> Verdict {
+        self.violations.push_back(now);
+        while self
+            .violations
+            .front()
+            .is_some_and(|t| now.saturating_duration_since(*t) > self.config.violation_window)
+        {
+            self.violations.pop_front();
+        }
+        if self.violations.len() >= self.config.max_violations {
+            Verdict::Kick(format!(
+                "{} bad messages in {}s (last: {})",
+                self.violations.len(),
+                self.config.violation_window.as_secs(),
+                why
+            ))
+        } else {
+            Verdict::Reject(why)
+        }
+    }
+
+    fn validate(&mut self, msg: ClientMessage, sent_tick: u64, now: Instant) -> Result<ClientMessage, String> {
+        match msg {
+            ClientMessage::Input { tick, axes } => {
+                self.check_tick(tick, now)?;
+                Ok(ClientMessage::Input { tick, axes: sanitize_axes(axes)? })
+            }
+            ClientMessage::StateUpdate { tick, x, y, z, yaw, pitch, sprint } => {
+                self.check_tick(tick, now)?;
+                for (field, v) in [("x", x), ("y", y), ("z", z)] {
+                    coord(field, v)?;
+                }
+                Ok(ClientMessage::StateUpdate {
+                    tick,
+                    x,
+                    y,
+                    z,
+                    yaw: wrap_angle("yaw", yaw)?,
+                    pitch: clamp_pitch(pitch)?,
+                    sprint,
+                })
+            }
+            ClientMessage::Fire { tick, origin, direction, weapon_id } => {
+                self.check_tick(tick, now)?;
+                for v in [origin.x, origin.y, origin.z] {
+                    coord("origin", v)?;
+                }
+                Ok(ClientMessage::Fire { tick, origin, direction: unit("direction", direction)?, weapon_id })
+            }
+            ClientMessage::SnapshotAck { tick } if tick > sent_tick => {
+                Err(format!("ack for tick {} but only sent up to {}", tick, sent_tick))
+            }
+            // Seat and class ids are checked against the sim when the request runs.
+            other => Ok(other),
+        }
+    }
+
+    fn check_tick(&mut self, tick: u32, now: Instant) -> Result<(), String> {
+        let Some((last, at)) = self.last_tick else {
+            self.last_tick = Some((tick, now));
+            return Ok(());
+        };
+        if tick < last.saturating_sub(TICK_REORDER_WINDOW) {
+            return Err(format!("tick {} went backwards from {}", tick, last));
+        }
+        let elapsed = now.saturating_duration_since(at).as_secs_f32();
+        let allowed = last.saturating_add((elapsed * MAX_TICK_RATE) as u32 + TICK_SLACK);
+        if tick > allowed {
+            return Err(format!("tick {} jumped ahead of {}", tick, last));
+        }
+        if tick > last {
+            self.last_tick = Some((tick, now));
+        }
+        Ok(())
+    }
+}
+
+/// Movement axes clamped to [-1, 1], look angles wrapped and clamped.
+pub fn sanitize_axes(axes: ClientAxes) -> Result<ClientAxes, String> {
+    Ok(ClientAxes {
+        forward: axis("forward", axes.forward)?,
+        right: axis("right", axes.right)?,
+        yaw: wrap_angle("yaw", axes.yaw)?,
+        pitch: clamp_pitch(axes.pitch)?,
+        ..axes
+    })
+}
+
+fn finite(field: &str, v: f32) -> Result<f32, String> {
+    if v.is_finite() {
+        Ok(v)
+    } else {
+        Err(format!("{} is {}", field, v))
+    }
+}
+
+fn axis(field: &str, v: f32) -> Result<f32, String> {
+    Ok(finite(field, v)?.clamp(-1.0, 1.0))
+}
+
+fn coord(field: &str, v: f32) -> Result<f32, String> {
+    if finite(field, v)?.abs() > MAX_COORD {
+        return Err(format!("{} {} out of range", field, v));
+    }
+    Ok(v)
+}
+
+fn wrap_angle(field: &str, v: f32) -> Result<f32, String> {
+    if finite(field, v)?.abs() > MAX_ANGLE {
+        return Err(format!("{} {} out of range", field, v));
+    }
+    // Into (-PI, PI].
+    Ok(PI - (PI - v).rem_euclid(TAU))
+}
+
+fn clamp_pitch(v: f32) -> Result<f32, String> {
+    Ok(finite("pitch", v)?.clamp(-FRAC_PI_2, FRAC_PI_2))
+}
+
+fn unit(field: &str, v: Vec3) -> Result<Vec3, String> {
+    let len = (finite(field, v.x)?.powi(2) + finite(field, v.y)?.powi(2) + finite(field, v.z)?.powi(2)).sqrt();
+    if len < 1e-6 {
+        return Err(format!("{} is zero", field));
+    }
+    Ok(Vec3 { x: v.x / len, y: v.y / len, z: v.z / len })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(tick: u32, forward: f32, yaw: f32) -> ClientMessage {
+        ClientMessage::Input {
+            tick,
+            axes: ClientAxes {
+                forward,
+                right: 0.0,
+                jump: false,
+                shoot: false,
+                reload: false,
+                sprint: false,
+                yaw,
+                pitch: 0.0,
+            },
+        }
+    }
+
+    #[test]
+    fn axes_are_clamped_and_nan_is_rejected() {
+        let now = Instant::now();
+        let mut guard = InputGuard::new(GuardConfig::default(), now);
+        match guard.check(input(1, 7.0, 3.0 * PI), 0, now) {
+            Verdict::Accept(ClientMessage::Input { axes, .. }) => {
+                assert_eq!(axes.forward, 1.0);
+                assert!((axes.yaw - PI).abs() < 1e-4);
+            }
+            other => panic!("{:?}", other),
+        }
+        assert!(matches!(guard.check(input(2, f32::NAN, 0.0), 0, now), Verdict::Reject(_)));
+        assert!(matches!(guard.check(input(3, 0.0, f32::INFINITY), 0, now), Verdict::Reject(_)));
+        assert!(matches!(guard.check(input(4, 0.0, 1e9), 0, now), Verdict::Reject(_)));
+    }
+
+    #[test]
+    fn ticks_must_be_sane() {
+        let now = Instant::now();
+        let mut guard = InputGuard::new(GuardConfig::default(), now);
+        assert!(matches!(guard.check(input(1000, 0.0, 0.0), 0, now), Verdict::Accept(_)));
+        // Slightly out of order is fine, far back or far ahead is not.
+        assert!(matches!(guard.check(input(990, 0.0, 0.0), 0, now), Verdict::Accept(_)));
+        assert!(matches!(guard.check(input(10, 0.0, 0.0), 0, now), Verdict::Reject(_)));
+        assert!(matches!(guard.check(input(2000, 0.0, 0.0), 0, now), Verdict::Reject(_)));
+        // A second later the counter may have moved on.
+        let later = now + Duration::from_secs(1);
+        assert!(matches!(guard.check(input(1400, 0.0, 0.0), 0, later), Verdict::Accept(_)));
+
+        let ack = |tick| ClientMessage::SnapshotAck { tick };
+        assert!(matches!(guard.check(ack(50), 50, later), Verdict::Accept(_)));
+        assert!(matches!(guard.check(ack(51), 50, later), Verdict::Reject(_)));
+    }
+
+    #[test]
+    fn floods_are_limited_then_kicked() {
+        let config = GuardConfig { messages_per_sec: 10.0, burst: 5.0, max_violations: 3, ..Default::default() };
+        let now = Instant::now();
+        let mut guard = InputGuard::new(config, now);
+        let verdicts: Vec<Verdict> = (0..8).map(|i| guard.check(input(i, 0.0, 0.0), 0, now)).collect();
+        assert!(verdicts[..5].iter().all(|v| matches!(v, Verdict::Accept(_))));
+        assert!(matches!(verdicts[5], Verdict::Reject(_)));
+        assert!(matches!(verdicts[6], Verdict::Reject(_)));
+        assert!(matches!(verdicts[7], Verdict::Kick(_)));
+
+        // The bucket refills at the sustained rate.
+        let mut guard = InputGuard::new(config, now);
+        for i in 0..50 {
+            let t = now + Duration::from_millis(100 * i);
+            assert!(matches!(guard.check(input(i as u32, 0.0, 0.0), 0, t), Verdict::Accept(_)));
+        }
+    }
+}
@@ -0,0 +1,213 @@
+// apps/client-tauri/src-tauri/src/vehicle.rs
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+use crate::entity::{EntityId, EntityStore, Health};
+use crate::protocol::{EntityKind, Transform, VehicleKind};
+
+// How close a player must be to a vehicle to get in.
+pub const ENTER_RANGE: f32 = 5.0;
+// Where a player is placed (to the vehicle's right) when getting out.
+pub const EXIT_OFFSET: f32 = 3.0;
+// Seat height above the vehicle origin.
+pub const SEAT_HEIGHT: f32 = 1.0;
+
+/// Hitscan weapon mounted on a gunner seat.
+#[derive(Debug, Clone, Copy)]
+pub struct VehicleWeapon {
+    pub damage_players: f32,
+    pub damage_vehicles: f32,
+    // Seconds between shots.
+    pub fire_rate: f32,
+    pub range: f32,
+}
+
+impl VehicleKind {
+    pub fn max_health(self) -> f32 {
+        match self {
+            VehicleKind::Jeep => 300.0,
+            VehicleKind::Tank => 1000.0,
+        }
+    }
+
+    pub fn seat_count(self) -> usize {
+        match self {
+            // Driver, mounted MG, passenger
+            VehicleKind::Jeep => 3,
+            // Driver, main gun
+            VehicleKind::Tank => 2,
+        }
+    }
+
+    /// Weapon for a seat, if any. Seat 0 is always the driver.
+    pub fn seat_weapon(self, seat: usize) -> Option<VehicleWeapon> {
+        match (self, seat) {
+            (VehicleKind::Jeep, 1) => Some(VehicleWeapon {
+                damage_players: 20.0,
+                damage_vehicles: 8.0,
+                fire_rate: 0.1,
+                range: 150.0,
+            }),
+            (VehicleKind::Tank, 1) => Some(VehicleWeapon {
+                damage_players: 120.0,
+                damage_vehicles: 350.0,
+                fire_rate: 2.5,
+                range: 300.0,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn max_speed(self) -> f32 {
+        match self {
+            VehicleKind::Jeep => 20.0,
+            VehicleKind::Tank => 10.0,
+        }
+    }
+
+    pub fn max_reverse_speed(self) -> f32 {
+        match self {
+            VehicleKind::Jeep => 8.0,
+            VehicleKind::Tank => 5.0,
+        }
+    }
+
+    pub fn acceleration(self) -> f32 {
+        match self {
+            VehicleKind::Jeep => 10.0,
+            VehicleKind::Tank => 4.0,
+        }
+    }
+
+    // Radians per second at full steering.
+    pub fn turn_rate(self) -> f32 {
+        match self {
+            VehicleKind::Jeep => 1.8,
+            VehicleKind::Tank => 0.9,
+        }
+    }
+
+    // Tracks can turn on the spot; wheels need speed to steer.
+    pub fn can_pivot(self) -> bool {
+        matches!(self, VehicleKind::Tank)
+    }
+
+    pub fn hit_radius(self) -> f32 {
+        match self {
+            VehicleKind::Jeep => 1.8,
+            VehicleKind::Tank => 2.5,
+        }
+    }
+
+    // Fraction of rifle damage the hull takes.
+    pub fn small_arms_factor(self) -> f32 {
+        match self {
+            VehicleKind::Jeep => 0.5,
+            VehicleKind::Tank => 0.0,
+        }
+    }
+}
+
+/// Map-defined place where a vehicle spawns and respawns after destruction.
+#[derive(Debug, Clone)]
+pub struct VehicleSpawn {
+    pub id: u32,
+    pub kind: VehicleKind,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub yaw: f32,
+    pub respawn_time: f32,
+}
+
+/// Vehicle-only state. Transform, health and team live in the entity store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Vehicle {
+    pub id: EntityId,
+    pub kind: VehicleKind,
+    pub spawn_id: u32,
+    // Signed speed along the heading (negative = reversing).
+    pub speed: f32,
+    // Occupant player entity per seat; seat 0 is the driver.
+    pub seats: Vec<Option<EntityId>>,
+    pub seat_cooldowns: Vec<f32>,
+    pub is_destroyed: bool,
+    pub respawn_timer: f32,
+    // Gets the kill credit for occupants if the vehicle is destroyed.
+    pub last_attacker: Option<EntityId>,
+}
+
+impl Vehicle {
+    pub fn new(id: EntityId, spawn: &VehicleSpawn) -> Self {
+        let kind = spawn.kind;
+        Self {
+            id,
+            kind,
+            spawn_id: spawn.id,
+            speed: 0.0,
+            seats: vec![None; kind.seat_count()],
+            seat_cooldowns: vec![0.0; kind.seat_count()],
+            is_destroyed: false,
+            respawn_timer: 0.0,
+            last_attacker: None,
+        }
+    }
+
+    pub fn seat_of(&self, player_id: EntityId) -> Option<usize> {
+        self.seats.iter().position(|s| *s == Some(player_id))
+    }
+}
+
+/// Hull center for a vehicle transform, used for hit tests and explosions.
+pub fn hull_center(t: &Transform) -> (f32, f32, f32) {
+    (t.x, t.y + SEAT_HEIGHT, t.z)
+}
+
+fn spawn_transform(spawn: &VehicleSpawn) -> Transform {
+    Transform { x: spawn.x, y: spawn.y, z: spawn.z, yaw: spawn.yaw, pitch: 0.0 }
+}
+
+impl EntityStore {
+    pub fn spawn_vehicle(&mut self, spawn: &VehicleSpawn) -> EntityId {
+        let id = self.spawn(EntityKind::Vehicle);
+        self.transforms.insert(id, spawn_transform(spawn));
+        self.healths.insert(id, Health::full(spawn.kind.max_health()));
+        self.vehicles.insert(id, Vehicle::new(id, spawn));
+        id
+    }
+
+    /// Put a destroyed vehicle back at its spawn, keeping its entity id.
+    pub fn respawn_vehicle(&mut self, id: EntityId, spawn: &VehicleSpawn) {
+        let Some(vehicle) = self.vehicles.get_mut(&id) else { return };
+        *vehicle = Vehicle::new(id, spawn);
+        self.transforms.insert(id, spawn_transform(spawn));
+        self.healths.insert(id, Health::full(spawn.kind.max_health()));
+        info!(target: "vehicles", kind = ?spawn.kind, vehicle = id, "respawned");
+    }
+
+    /// Whether a teammate of `player` is aboard the vehicle.
+    pub fn friendly_vehicle(&self, player: EntityId, vehicle_id: EntityId) -> bool {
+        self.vehicles
+            .get(&vehicle_id)
+            .is_some_and(|v| v.seats.iter().flatten().any(|occupant| self.teammates(player, *occupant)))
+    }
+
+    pub fn damage_vehicle(&mut self, id: EntityId, damage: f32, attacker: EntityId) {
+        let Some(vehicle) = self.vehicles.get_mut(&id) else { return };
+        if vehicle.is_destroyed || damage <= 0.0 {
+            return;
+        }
+        vehicle.last_attacker = Some(attacker);
+        let kind = vehicle.kind;
+        if let Some(hp) = self.damage(id, damage) {
+            debug!(target: "vehicles", ?kind, vehicle = id, damage, hp, "took damage");
+        }
+    }
+}
+
+/// One-shot enter/exit request, queued by the network layer so it is not
+/// overwritten by the next `Input` in the input map.
+#[derive(Debug, Clone, Copy)]
+pub enum VehicleRequest {
+    Enter { vehicle_id: EntityId, seat: Option<u8> },
+    Exit,
+}
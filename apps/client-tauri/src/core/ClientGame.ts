@@ -17,6 +17,7 @@ import { createLocalPlayer } from './local-player';
 
 import { NetworkManager } from '../managers/NetworkManager';
 import { UIManager } from '../managers/UIManager';
+import { ServerMenu } from '../managers/ServerMenu';
 import { Reconciler } from '../systems/Reconciler';
 import { updateGameFrame } from '../systems/updateGameFrame';
 import { updateWorldRender } from '../world/worldRender';
@@ -34,6 +35,7 @@ export class ClientGame {
 
   private input: InputManager;
   private ui: UIManager;
+  private menu: ServerMenu;
   private hud: HUDUpdater;
   public reconciler: Reconciler;
   private weaponSystem: WeaponSystem;
@@ -42,6 +44,7 @@ export class ClientGame {
 
   private localEntityId: number = -1;
   private lastRtt = 0;
+  private connected = false;
 
   private readonly SEND_INTERVAL = 1 / 30; // 30 Hz input send
   private readonly INTERPOLATION_DELAY_MS = 100;
@@ -74,7 +77,9 @@ this.snapshotHandler = new SnapshotHandler(this.hud);
     this.input.setInteraction(true);
 
     this.initNetworkCallbacks();
-    this.net.connect('ws://localhost:8080');
+
+    // Nothing to connect to until the player hosts or picks a server.
+    this.menu = new ServerMenu((url: string) => this.net.connect(url));
 
     // Local player ECS entity (all components are set up in one place)
     this.localEntityId = createLocalPlayer(world);
@@ -88,10 +93,16 @@ this.snapshotHandler = new SnapshotHandler(this.hud);
   private initNetworkCallbacks() {
     this.net.onConnected = () => {
       console.log('Connected to server');
+      this.connected = true;
     };
 
     this.net.onDisconnected = () => {
       console.log('Disconnected from server');
+      // A failed connect attempt is reported by the menu itself.
+      if (!this.connected) return;
+      this.connected = false;
+      this.ui.setDeployMode(true);
+      this.menu.show('Disconnected from server');
     };
 
     // Hit marker now goes through HUD façade
@@ -55,7 +55,7 @@ export class InputManager {
         return {
             type: 'input',
             tick,
-            axes: { forward:0, right:0, jump:false, shoot:false, reload:false, yaw: this.yaw, pitch: this.pitch }
+            axes: { forward:0, right:0, jump:false, shoot:false, reload:false, sprint:false, yaw: this.yaw, pitch: this.pitch }
         };
     }
 
@@ -76,6 +76,7 @@ export class InputManager {
         jump: this.keys.has('Space'),
         shoot: this.buttons.has(0),
         reload: this.keys.has('KeyR'),
+        sprint: this.keys.has('ShiftLeft'),
         yaw: this.yaw,
         pitch: this.pitch
       }
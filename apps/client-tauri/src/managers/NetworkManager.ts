@@ -1,10 +1,13 @@
 // apps/client-tauri/src/managers/NetworkManager.ts
 
 import { WebSocketAdapter, type NetworkAdapter } from '@bf42lite/net';
+import { PROTOCOL_VERSION } from '@bf42lite/protocol';
 import type {
+  ClientHello,
   ClientInput,
   ClientFire,
   ClientMessage,
+  ClientStateUpdate,
   Snapshot,
 } from '@bf42lite/protocol';
 import type { SimWorld } from '@bf42lite/engine-core';
@@ -41,6 +44,12 @@ export class NetworkManager {
   private reconciler: Reconciler;
 
   private myServerId = -1;
+  // Given by the Rust host on join; lets a reconnect reclaim our player.
+  private sessionToken: string | null = null;
+  // Server the session token belongs to.
+  private serverUrl: string | null = null;
+  // Sent in the hello on every (re)connect.
+  private playerName = localStorage.getItem('bf42lite.playerName') ?? 'Soldier';
   private serverToLocal = new Map<number, number>();
 
   // Monotonic counter used by decodeServerMessage (for timestamps/ticks)
@@ -55,6 +64,7 @@ export class NetworkManager {
 
     this.net.onConnect(() => {
       log.info('NET', 'Connected');
+      this.sendHello();
       this.onConnected();
     });
 
@@ -68,8 +78,16 @@ export class NetworkManager {
 
   // --- Connection ---
 
-  connect(url: string): void {
-    this.net.connect(url);
+  // Resolves once connected; rejects if the server can't be reached.
+  connect(url: string): Promise<void> {
+    // A session only means something to the server that issued it.
+    if (url !== this.serverUrl) this.sessionToken = null;
+    this.serverUrl = url;
+    if (this.sessionToken) {
+      const sep = url.includes('?') ? '&' : '?';
+      url = `${url}${sep}session=${encodeURIComponent(this.sessionToken)}`;
+    }
+    return this.net.connect(url);
   }
 
   // --- Incoming messages ---
@@ -95,6 +113,10 @@ export class NetworkManager {
       case 'snapshot': {
         const snapshot = decoded.snapshot;
 
+        if (decoded.sessionToken) {
+          this.sessionToken = decoded.sessionToken;
+        }
+
         // Remote entities (ECS + interpolation buffers)
         RemoteEntitySync.apply(
           snapshot,
@@ -135,6 +157,21 @@ export class NetworkManager {
 
   // --- Outgoing: input / fire / spawn ---
 
+  setPlayerName(name: string): void {
+    this.playerName = name;
+    localStorage.setItem('bf42lite.playerName', name);
+  }
+
+  /** Handshake: must be the first message the host sees. */
+  private sendHello(): void {
+    const msg: ClientHello = {
+      type: 'hello',
+      protocolVersion: PROTOCOL_VERSION,
+      name: this.playerName,
+    };
+    this.net.send(msg);
+  }
+
   /** Alias kept for existing code: client uses `net.send(cmd)` */
   send(cmd: ClientInput): void {
     this.sendInput(cmd);
@@ -144,6 +181,11 @@ export class NetworkManager {
     this.net.send(cmd);
   }
 
+  /** Client-authoritative movement: report our own transform. */
+  sendStateUpdate(msg: ClientStateUpdate): void {
+    this.net.send(msg);
+  }
+
   sendFire(
     origin: { x: number; y: number; z: number },
     direction: { x: number; y: number; z: number },
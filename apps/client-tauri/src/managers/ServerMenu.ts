@@ -0,0 +1,232 @@
+// apps/client-tauri/src/managers/ServerMenu.ts
+
+import { PROTOCOL_VERSION } from '@bf42lite/protocol';
+
+// Host / join menu, shown until we're connected. Hosting and LAN discovery go
+// through the Tauri commands in src-tauri/src/lib.rs; in a plain browser
+// (pnpm dev:web) only joining by address is available.
+
+// Mirrors host::ServerStatus.
+export type ServerStatus = {
+  state: 'stopped' | 'running' | 'failed';
+  addr: string | null;
+  players: number | null;
+  uptime_secs: number;
+  error: string | null;
+};
+
+// Mirrors discovery::LanServer.
+export type LanServer = {
+  addr: string;
+  info: {
+    name: string;
+    map: string;
+    mode: string;
+    players: number;
+    max_players: number;
+    protocol_version: number;
+    port: number;
+  };
+  ping_ms: number;
+};
+
+// The parts of window.__TAURI__ (withGlobalTauri) we use.
+type TauriGlobals = {
+  core: { invoke<T>(cmd: string, args?: Record<string, unknown>): Promise<T> };
+  event: { listen<T>(event: string, handler: (e: { payload: T }) => void): Promise<() => void> };
+};
+
+const tauri = (window as unknown as { __TAURI__?: TauriGlobals }).__TAURI__;
+
+const DEFAULT_PORT = 8080;
+const LAST_SERVER_KEY = 'bf42lite.lastServer';
+
+/** WebSocket URL for "host", "host:port" or a full ws:// URL. */
+export function toWsUrl(addr: string): string {
+  const a = addr.trim();
+  if (/^wss?:\/\//.test(a)) return a;
+  return /:\d+$/.test(a) ? `ws://${a}` : `ws://${a}:${DEFAULT_PORT}`;
+}
+
+// Where to reach our own server: its bind address, or loopback if it
+// listens on every interface.
+function hostedUrl(addr: string): string {
+  const split = addr.lastIndexOf(':');
+  const ip = addr.slice(0, split);
+  const port = addr.slice(split + 1);
+  const local = ip === '0.0.0.0' || ip === '[::]' ? '127.0.0.1' : ip;
+  return `ws://${local}:${port}`;
+}
+
+export class ServerMenu {
+  private ui = {
+    root: document.getElementById('server-menu'),
+    message: document.getElementById('menu-message'),
+    hostPanel: document.getElementById('host-panel'),
+    hostStatus: document.getElementById('host-status'),
+    hostBtn: document.getElementById('btn-host') as HTMLButtonElement | null,
+    stopBtn: document.getElementById('btn-stop') as HTMLButtonElement | null,
+    lanPanel: document.getElementById('lan-panel'),
+    lanList: document.getElementById('lan-list'),
+    refreshBtn: document.getElementById('btn-refresh') as HTMLButtonElement | null,
+    addrInput: document.getElementById('join-addr') as HTMLInputElement | null,
+    joinBtn: document.getElementById('btn-join') as HTMLButtonElement | null,
+    backBtn: document.getElementById('btn-menu-back'),
+    openBtn: document.getElementById('btn-servers'),
+  };
+
+  private status: ServerStatus | null = null;
+  private busy = false;
+
+  // Connects to a ws:// URL; rejects if the server can't be reached.
+  private onJoin: (url: string) => Promise<void>;
+
+  constructor(onJoin: (url: string) => Promise<void>) {
+    this.onJoin = onJoin;
+
+    if (this.ui.addrInput) {
+      this.ui.addrInput.value = localStorage.getItem(LAST_SERVER_KEY) ?? `localhost:${DEFAULT_PORT}`;
+    }
+    this.initListeners();
+
+    if (tauri) {
+      tauri.event.listen<ServerStatus>('server-state', (e) => this.showStatus(e.payload));
+      // Hosting may have started (--host) before we were listening.
+      tauri.core.invoke<ServerStatus>('server_status').then((s) => this.showStatus(s));
+      this.refreshLan();
+    } else {
+      this.ui.hostPanel?.classList.add('hidden');
+      this.ui.lanPanel?.classList.add('hidden');
+    }
+  }
+
+  private initListeners() {
+    this.ui.hostBtn?.addEventListener('click', () => this.host());
+    this.ui.stopBtn?.addEventListener('click', () => this.stopHosting());
+    this.ui.refreshBtn?.addEventListener('click', () => this.refreshLan());
+    this.ui.joinBtn?.addEventListener('click', () => this.joinTyped());
+    this.ui.addrInput?.addEventListener('keydown', (e) => {
+      if (e.key === 'Enter') this.joinTyped();
+    });
+    // From the deploy screen, while connected.
+    this.ui.openBtn?.addEventListener('click', () => this.show('', true));
+    this.ui.backBtn?.addEventListener('click', () => this.hide());
+  }
+
+  // `canGoBack`: still connected, so the menu can be closed again.
+  public show(message = '', canGoBack = false) {
+    this.setMessage(message);
+    this.ui.backBtn?.classList.toggle('hidden', !canGoBack);
+    this.ui.root?.classList.remove('hidden');
+    document.exitPointerLock();
+    if (tauri) this.refreshLan();
+  }
+
+  public hide() {
+    this.ui.root?.classList.add('hidden');
+  }
+
+  private setMessage(text: string) {
+    if (this.ui.message) this.ui.message.innerText = text;
+  }
+
+  // --- Hosting ---
+
+  private async host() {
+    if (!tauri || this.busy) return;
+    this.busy = true;
+    try {
+      // Already hosting (e.g. started with --host): just play on it.
+      const status =
+        this.status?.state === 'running'
+          ? this.status
+          : await tauri.core.invoke<ServerStatus>('host_server');
+      this.showStatus(status);
+      if (status.addr) await this.join(hostedUrl(status.addr));
+    } catch (e) {
+      this.setMessage(`Couldn't host: ${e}`);
+    } finally {
+      this.busy = false;
+    }
+  }
+
+  private async stopHosting() {
+    if (!tauri) return;
+    try {
+      await tauri.core.invoke('stop_server');
+    } catch (e) {
+      this.setMessage(`Couldn't stop the server: ${e}`);
+    }
+  }
+
+  private showStatus(status: ServerStatus) {
+    this.status = status;
+    const running = status.state === 'running';
+
+    if (this.ui.hostStatus) {
+      if (running) {
+        this.ui.hostStatus.innerText = `Hosting on ${status.addr} (${status.players ?? 0} players)`;
+      } else if (status.state === 'failed') {
+        this.ui.hostStatus.innerText = `Server stopped: ${status.error ?? 'unknown error'}`;
+      } else {
+        this.ui.hostStatus.innerText = 'Not hosting';
+      }
+    }
+    if (this.ui.hostBtn) this.ui.hostBtn.innerText = running ? 'PLAY ON YOUR SERVER' : 'HOST GAME';
+    this.ui.stopBtn?.classList.toggle('hidden', !running);
+  }
+
+  // --- Joining ---
+
+  private joinTyped() {
+    const addr = this.ui.addrInput?.value.trim();
+    if (!addr) return;
+    localStorage.setItem(LAST_SERVER_KEY, addr);
+    this.join(toWsUrl(addr));
+  }
+
+  private async join(url: string) {
+    this.setMessage(`Connecting to ${url}...`);
+    try {
+      await this.onJoin(url);
+      this.hide();
+    } catch {
+      this.setMessage(`Couldn't connect to ${url}`);
+    }
+  }
+
+  private async refreshLan() {
+    if (!tauri || !this.ui.lanList) return;
+    const list = this.ui.lanList;
+    list.innerText = 'Searching...';
+
+    let servers: LanServer[];
+    try {
+      servers = await tauri.core.invoke<LanServer[]>('list_lan_servers');
+    } catch (e) {
+      list.innerText = `LAN search failed: ${e}`;
+      return;
+    }
+
+    list.innerHTML = '';
+    if (!servers.length) {
+      list.innerText = 'No servers found';
+      return;
+    }
+    servers.forEach((s) => {
+      const row = document.createElement('button');
+      row.classList.add('lan-row');
+      row.innerText =
+        `${s.info.name} — ${s.info.map} (${s.info.mode}) ` +
+        `${s.info.players}/${s.info.max_players} · ${s.ping_ms}ms`;
+
+      if (s.info.protocol_version !== PROTOCOL_VERSION) {
+        row.disabled = true;
+        row.title = 'Different game version';
+      } else {
+        row.addEventListener('click', () => this.join(toWsUrl(s.addr)));
+      }
+      list.appendChild(row);
+    });
+  }
+}
@@ -164,6 +164,10 @@ import type {
       if (typeof msg.your_id === 'number') {
         decoded.yourId = msg.your_id;
       }
+
+      if (typeof msg.session_token === 'string') {
+        decoded.sessionToken = msg.session_token;
+      }
   
       return decoded;
     }
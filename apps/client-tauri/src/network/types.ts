@@ -22,6 +22,7 @@ export interface DecodedSnapshotMessage {
   type: 'snapshot';
   snapshot: Snapshot;
   yourId?: number;        // Present when coming from Rust envelope { your_id, snapshot }
+  sessionToken?: string;  // First Rust envelope only; reconnect with ?session=<token>
 }
 
 export interface DecodedWelcomeMessage {
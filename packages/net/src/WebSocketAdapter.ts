@@ -17,6 +17,12 @@ export class WebSocketAdapter implements NetworkAdapter {
   }
 
   async connect(url: string): Promise<void> {
+    // Switching servers: the old socket's close isn't a disconnect from the new one.
+    if (this.socket) {
+      this.socket.onclose = null;
+      this.socket.onmessage = null;
+      this.socket.close();
+    }
     return new Promise((resolve, reject) => {
       this.socket = new WebSocket(url);
       this.socket.binaryType = 'arraybuffer';
@@ -11,6 +11,7 @@ export interface ClientInput {
     jump: boolean;
     shoot: boolean;
     reload: boolean;
+    sprint?: boolean;
   };
 }
 
@@ -2,6 +2,18 @@ import { z } from 'zod';
 
 // --- 1. CLIENT -> SERVER ---
 
+// Must match PROTOCOL_VERSION in the Rust host (src-tauri/src/protocol.rs).
+export const PROTOCOL_VERSION = 3;
+
+// First message on every connection; the host rejects version mismatches.
+export const ClientHelloSchema = z.object({
+  type: z.literal('hello'),
+  protocolVersion: z.number(),
+  name: z.string(),
+  requestedTeam: z.enum(['TeamA', 'TeamB']).optional(), // omit to auto-balance
+  compact: z.boolean().optional(), // ask for the compact snapshot encoding
+});
+
 export const ClientInputSchema = z.object({
   type: z.literal('input'),
   tick: z.number(),
@@ -11,11 +23,25 @@ export const ClientInputSchema = z.object({
     jump: z.boolean(),
     shoot: z.boolean(),
     reload: z.boolean(), // <--- ADDED
+    sprint: z.boolean().optional().default(false),
     yaw: z.number(),
     pitch: z.number(),
   }),
 });
 
+// Client-authoritative movement (net.movement = "client" in bf42lite.toml,
+// or --set net.movement=client)
+export const ClientStateUpdateSchema = z.object({
+  type: z.literal('update'),
+  tick: z.number(),
+  x: z.number(),
+  y: z.number(),
+  z: z.number(),
+  yaw: z.number(),
+  pitch: z.number().optional().default(0),
+  sprint: z.boolean().optional().default(false),
+});
+
 export const ClientFireSchema = z.object({
   type: z.literal('fire'),
   tick: z.number(),
@@ -29,10 +55,32 @@ export const SpawnRequestSchema = z.object({
   classId: z.number(), // or z.enum([...]) if you have specific classes
 });
 
+export const EnterVehicleSchema = z.object({
+  type: z.literal('enter_vehicle'),
+  vehicleId: z.number(),
+  seat: z.number().optional(), // omit for first free seat
+});
+
+export const ExitVehicleSchema = z.object({
+  type: z.literal('exit_vehicle'),
+});
+
+// Newest snapshot tick the client holds; the host then sends deltas against
+// it instead of full snapshots. tick 0 asks for a full snapshot again.
+export const SnapshotAckSchema = z.object({
+  type: z.literal('ack'),
+  tick: z.number(),
+});
+
 export const ClientMessageSchema = z.union([
+  ClientHelloSchema,
   ClientInputSchema,
+  ClientStateUpdateSchema,
   ClientFireSchema,
   SpawnRequestSchema,
+  EnterVehicleSchema,
+  ExitVehicleSchema,
+  SnapshotAckSchema,
 ]);
 
 // --- 2. SERVER -> CLIENT BASE TYPES ---
@@ -46,6 +94,14 @@ export const EntityStateSchema = z.object({
   rot: z.number(),
   health: z.number(),
   isDead: z.boolean(),
+  // Rust host: 'Player' | 'Vehicle' | 'Projectile'
+  kind: z.any().optional(),
+  name: z.string().nullable().optional(),
+  // Smoothed round trip to the player's client, players only
+  latency: z
+    .object({ rtt_ms: z.number(), jitter_ms: z.number() })
+    .nullable()
+    .optional(),
   // extra fields are allowed
 }).catchall(z.any());
 
@@ -75,6 +131,32 @@ export const FlagSnapshotSchema = z.object({
   capture: z.number(),
 });
 
+// Position is in the entity with the same id.
+export const ProjectileSnapshotSchema = z.object({
+  id: z.number(),
+  kind: z.any(), // 'Grenade' | 'RifleGrenade'
+  owner: z.number(),
+  vx: z.number(),
+  vy: z.number(),
+  vz: z.number(),
+});
+
+// Transform and health are in the entity with the same id.
+export const VehicleSnapshotSchema = z.object({
+  id: z.number(),
+  kind: z.any(), // 'Jeep' | 'Tank'
+  seats: z.array(z.number().nullable()), // seat 0 = driver
+  destroyed: z.boolean(),
+});
+
+export const ScoreboardEntrySchema = z.object({
+  eid: z.number(),
+  name: z.string(),
+  team: z.any(),
+  kills: z.number(),
+  deaths: z.number(),
+});
+
 // --- 4. SNAPSHOT (UNION OF OLD + NEW FIELDS) ---
 
 export const SnapshotSchema = z.object({
@@ -91,6 +173,15 @@ export const SnapshotSchema = z.object({
 
   // New Rust-host conquest flags
   flags: z.array(FlagSnapshotSchema).optional().default([]),
+
+  // Rust-host grenades / projectiles in flight
+  projectiles: z.array(ProjectileSnapshotSchema).optional().default([]),
+
+  // Rust-host vehicles
+  vehicles: z.array(VehicleSnapshotSchema).optional().default([]),
+
+  // Rust-host scoreboard, sorted by kills
+  scoreboard: z.array(ScoreboardEntrySchema).optional().default([]),
 });
 
 // --- 5. OTHER SERVER → CLIENT MESSAGES ---
@@ -114,8 +205,10 @@ export const ServerMessageSchema = z.union([
   HitConfirmedSchema,
 ]);
 
+export type ClientHello = z.infer<typeof ClientHelloSchema>;
 export type ClientInput = z.infer<typeof ClientInputSchema>;
 export type ClientFire = z.infer<typeof ClientFireSchema>;
+export type ClientStateUpdate = z.infer<typeof ClientStateUpdateSchema>;
 export type Snapshot = z.infer<typeof SnapshotSchema>;
 export type EntityState = z.infer<typeof EntityStateSchema>;
 export type ServerMessage = z.infer<typeof ServerMessageSchema>;